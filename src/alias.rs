@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use tt_core::record::{DateTime, Local};
+
+/// Expands the note whose first word is an alias into the aliased note.
+///
+/// The placeholders `{1}`, `{2}`... of the aliased note are replaced by the following words of the note,
+/// `{date}` and `{time}` by the current date and time. The words not used by placeholders are appended.
+pub fn expand(aliases: &HashMap<String, String>, note: &str, now: DateTime<Local>) -> Result<String, String> {
+    let mut words = note.split_whitespace();
+    let template = match words.next().and_then(|name| aliases.get(&name.to_lowercase())) {
+        Some(template) => template,
+        None => return Ok(note.to_string()),
+    };
+    let args = words.collect::<Vec<_>>();
+    let mut used = vec![false; args.len()];
+
+    let mut expanded = String::new();
+    let mut rest = template.as_str();
+    while let Some(open) = rest.find('{') {
+        let close = match rest[open..].find('}') {
+            Some(close) => open + close,
+            None => break,
+        };
+        expanded.push_str(&rest[..open]);
+
+        let name = &rest[open + 1..close];
+        match name {
+            "date" => expanded.push_str(&now.format("%Y-%m-%d").to_string()),
+            "time" => expanded.push_str(&now.format("%H:%M").to_string()),
+            _ => match name.parse::<usize>() {
+                Ok(number) if number > 0 => {
+                    let arg = args.get(number - 1)
+                        .ok_or_else(|| format!("Alias {:?} requires argument {{{}}}", template, number))?;
+                    expanded.push_str(arg);
+                    used[number - 1] = true;
+                },
+                _ => expanded.push_str(&rest[open..=close]),
+            },
+        }
+        rest = &rest[close + 1..];
+    }
+    expanded.push_str(rest);
+
+    for (arg, _) in args.iter().zip(used).filter(|(_, used)| !used) {
+        expanded.push(' ');
+        expanded.push_str(arg);
+    }
+    Ok(expanded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tt_core::record::TimeZone;
+
+    fn aliases() -> HashMap<String, String> {
+        let mut aliases = HashMap::new();
+        aliases.insert("standup".to_string(), "Meetings daily standup".to_string());
+        aliases.insert("review".to_string(), "Day work review PR-{1}".to_string());
+        aliases.insert("log".to_string(), "Notes {date} {time}".to_string());
+        aliases
+    }
+
+    #[test]
+    fn expand_alias() {
+        let now = Local.ymd(2018, 12, 3).and_hms(9, 30, 0);
+        let aliases = aliases();
+        assert_eq!(Ok("Meetings daily standup".to_string()), expand(&aliases, "standup", now));
+        assert_eq!(Ok("Meetings daily standup".to_string()), expand(&aliases, "Standup", now));
+        assert_eq!(Ok("Meetings daily standup late".to_string()), expand(&aliases, "standup late", now));
+        assert_eq!(Ok("Day work review PR-42".to_string()), expand(&aliases, "review 42", now));
+        assert_eq!(Ok("Notes 2018-12-03 09:30".to_string()), expand(&aliases, "log", now));
+        assert_eq!(Ok("Day work task1".to_string()), expand(&aliases, "Day work task1", now));
+        assert_eq!(Ok("".to_string()), expand(&aliases, "", now));
+    }
+
+    #[test]
+    fn expand_alias_without_argument() {
+        let now = Local.ymd(2018, 12, 3).and_hms(9, 30, 0);
+        assert!(expand(&aliases(), "review", now).is_err());
+    }
+}
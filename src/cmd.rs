@@ -1,10 +1,30 @@
-use std::collections::BTreeMap;
+use std::cmp::Reverse;
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
 use std::fmt::Display;
+use std::fs;
+use std::io::{self, Write};
+use std::process;
 use clap::ArgMatches;
+use regex::Regex;
 use tt_core::record::{Record, Local, Date, DateTime, Datelike, Timelike, TimeZone, Duration};
 use tt_core::journal::{Journal, file::{FileJournal, Item}};
-use crate::report::ReportNode;
-use crate::settings::Settings;
+use crate::alias;
+use crate::duration::{DurationArg, parse_duration};
+use crate::edit::edit_records;
+use crate::export;
+use crate::fix::JournalFixer;
+use crate::git;
+use crate::ical;
+use crate::import::{self, Format};
+use crate::index::{JournalIndex, JournalTail};
+use crate::meta::Meta;
+use crate::pomodoro::{self, Pomodoro};
+use crate::report::{self, ReportNode};
+use crate::selector::{Selector, Target, record_id};
+use crate::settings::{Settings, Overlap};
+use crate::summary::{self, Entry, Period};
+use crate::timesheet::Grouping;
 
 #[derive(Default)]
 pub struct Cmd {
@@ -64,6 +84,265 @@ impl Cmd {
         desc: "Setup record attribute"
     };
 
+    pub const FIX: Cmd = Cmd {
+        upcase_name: "FIX",
+        name: "fix",
+        short: "",
+        desc: "Repair journal: reorder records, close dangling ones, trim overlaps and normalize spacing"
+    };
+
+    pub const EDIT: Cmd = Cmd {
+        upcase_name: "EDIT",
+        name: "edit",
+        short: "",
+        desc: "Edit records in the text editor from $VISUAL or $EDITOR, today records by default"
+    };
+
+    pub const IMPORT: Cmd = Cmd {
+        upcase_name: "IMPORT",
+        name: "import",
+        short: "",
+        desc: "Import records from other time trackers, skipping the ones already in the journal"
+    };
+
+    pub const IMPORT_CALENDAR: Cmd = Cmd {
+        upcase_name: "IMPORT_CALENDAR",
+        name: "import-calendar",
+        short: "",
+        desc: "Propose records for the events of an iCalendar file and add the accepted ones not overlapping the journal"
+    };
+
+    pub const GIT_LOG: Cmd = Cmd {
+        upcase_name: "GIT_LOG",
+        name: "git-log",
+        short: "",
+        desc: "List commits authored on the dates next to the records running at the commit time"
+    };
+
+    pub const HOOK: Cmd = Cmd {
+        upcase_name: "HOOK",
+        name: "hook",
+        short: "",
+        desc: "Follow git branch checkouts with records"
+    };
+
+    pub const INSTALL: Cmd = Cmd {
+        upcase_name: "INSTALL",
+        name: "install",
+        short: "",
+        desc: "Install the post-checkout hook starting a record for the checked out branch"
+    };
+
+    pub const CHECKOUT: Cmd = Cmd {
+        upcase_name: "CHECKOUT",
+        name: "checkout",
+        short: "",
+        desc: "Stop the running record and start the one of the branch, called by the hook"
+    };
+
+    pub const BRANCH: Cmd = Cmd {
+        upcase_name: "BRANCH",
+        name: "branch",
+        short: "",
+        desc: "The checked out branch"
+    };
+
+    pub const SERVE: Cmd = Cmd {
+        upcase_name: "SERVE",
+        name: "serve",
+        short: "",
+        desc: "Serve the JSON API for editor plugins and widgets on localhost"
+    };
+
+    pub const EXPORT: Cmd = Cmd {
+        upcase_name: "EXPORT",
+        name: "export",
+        short: "",
+        desc: "Print stopped records as calendar events or a timesheet, all records by default"
+    };
+
+    pub const TUI: Cmd = Cmd {
+        upcase_name: "TUI",
+        name: "tui",
+        short: "",
+        desc: "Show full-screen terminal interface"
+    };
+
+    pub const IDLE: Cmd = Cmd {
+        upcase_name: "IDLE",
+        name: "idle",
+        short: "",
+        desc: "How to stop the record running longer than the idle threshold: \"all\", \"rest\" or the stop time, for example \"17:30\""
+    };
+
+    pub const FLAGGED: Cmd = Cmd {
+        upcase_name: "FLAGGED",
+        name: "flagged",
+        short: "",
+        desc: "Show records of all dates whose activity was capped by max_activity, until they are changed by set"
+    };
+
+    pub const RESUME: Cmd = Cmd {
+        upcase_name: "RESUME",
+        name: "resume",
+        short: "",
+        desc: "Start a new record with a recent note matching the query or chosen from a menu"
+    };
+
+    pub const POMODORO: Cmd = Cmd {
+        upcase_name: "POMODORO",
+        name: "pomodoro",
+        short: "",
+        desc: "Track work intervals with breaks between them, ringing the bell at transitions"
+    };
+
+    pub const STATUS: Cmd = Cmd {
+        upcase_name: "STATUS",
+        name: "status",
+        short: "",
+        desc: "Show the running record with its elapsed and remaining time"
+    };
+
+    pub const COMPLETIONS: Cmd = Cmd {
+        upcase_name: "COMPLETIONS",
+        name: "completions",
+        short: "",
+        desc: "Print the shell completion script"
+    };
+
+    pub const NOTES: Cmd = Cmd {
+        upcase_name: "NOTES",
+        name: "notes",
+        short: "",
+        desc: "Print the next words of frequent recent notes starting with the given words"
+    };
+
+    pub const SHELL: Cmd = Cmd {
+        upcase_name: "SHELL",
+        name: "shell",
+        short: "",
+        desc: "The shell to generate the script for"
+    };
+
+    pub const QUERY: Cmd = Cmd {
+        upcase_name: "QUERY",
+        name: "query",
+        short: "",
+        desc: "Text found in recent notes ignoring case, for example: \"task2\", \"day work\""
+    };
+
+    pub const FOR: Cmd = Cmd {
+        upcase_name: "FOR",
+        name: "for",
+        short: "",
+        desc: "Intended duration of the record, for example: \"45m\", \"1h30m\""
+    };
+
+    pub const WAIT: Cmd = Cmd {
+        upcase_name: "WAIT",
+        name: "wait",
+        short: "w",
+        desc: "Wait until the intended duration elapses and stop the record"
+    };
+
+    pub const FROM: Cmd = Cmd {
+        upcase_name: "FROM",
+        name: "from",
+        short: "",
+        desc: "Format of the imported file"
+    };
+
+    pub const FILE: Cmd = Cmd {
+        upcase_name: "FILE",
+        name: "file",
+        short: "",
+        desc: "Path to the file"
+    };
+
+    pub const FORMAT: Cmd = Cmd {
+        upcase_name: "FORMAT",
+        name: "format",
+        short: "",
+        desc: "Format of the exported records"
+    };
+
+    pub const BY: Cmd = Cmd {
+        upcase_name: "BY",
+        name: "by",
+        short: "",
+        desc: "Rows of the exported timesheet: a row for each record or for each project of each day"
+    };
+
+    pub const REPO: Cmd = Cmd {
+        upcase_name: "REPO",
+        name: "repo",
+        short: "",
+        desc: "Path to the git repository, the current directory by default"
+    };
+
+    pub const APPEND: Cmd = Cmd {
+        upcase_name: "APPEND",
+        name: "append",
+        short: "",
+        desc: "Append the commit summaries to the notes of the running records"
+    };
+
+    pub const SUMMARY: Cmd = Cmd {
+        upcase_name: "SUMMARY",
+        name: "summary",
+        short: "",
+        desc: "Summarize the week or the month and compare it with the previous one"
+    };
+
+    pub const WEEK: Cmd = Cmd {
+        upcase_name: "WEEK",
+        name: "week",
+        short: "w",
+        desc: "Summarize the week by days, the default"
+    };
+
+    pub const MONTH: Cmd = Cmd {
+        upcase_name: "MONTH",
+        name: "month",
+        short: "m",
+        desc: "Summarize the month by weeks"
+    };
+
+    pub const PORT: Cmd = Cmd {
+        upcase_name: "PORT",
+        name: "port",
+        short: "p",
+        desc: "Port of the localhost address to listen on, 0 for a free port chosen by the system"
+    };
+
+    pub const TO: Cmd = Cmd {
+        upcase_name: "TO",
+        name: "to",
+        short: "",
+        desc: "Last start date of the exported records, for example: \"2018-08-25\", \"08-25\", \"25\", \"now\""
+    };
+
+    pub const WORK: Cmd = Cmd {
+        upcase_name: "WORK",
+        name: "work",
+        short: "",
+        desc: "Duration of the work interval, for example: \"25\", \"50m\", \"1h\""
+    };
+
+    pub const BREAK: Cmd = Cmd {
+        upcase_name: "BREAK",
+        name: "break",
+        short: "",
+        desc: "Duration of the break after the work interval, \"0\" for no break"
+    };
+
+    pub const CYCLES: Cmd = Cmd {
+        upcase_name: "CYCLES",
+        name: "cycles",
+        short: "",
+        desc: "Number of work intervals"
+    };
+
     pub const NOTE: Cmd = Cmd {
         upcase_name: "NOTE",
         name: "note",
@@ -82,7 +361,7 @@ impl Cmd {
         upcase_name: "TIME",
         name: "time",
         short: "",
-        desc: "The record start time, for example: \"14:09:21\", \"14:09\", \"9\", \"now\""
+        desc: "The record start time or its shift, for example: \"14:09:21\", \"14:09\", \"9\", \"now\", \"+1h\", \"-30m\""
     };
 
     pub const DATETIME: Cmd = Cmd {
@@ -92,18 +371,25 @@ impl Cmd {
         desc: "The record start datetime, for example: \"2018-08-25 14:09:21\""
     };
 
+    pub const END: Cmd = Cmd {
+        upcase_name: "END",
+        name: "end",
+        short: "e",
+        desc: "The record end time or datetime, for example: \"17:40\", \"2018-08-25 17:40\", \"now\""
+    };
+
     pub const ACTIVITY: Cmd = Cmd {
         upcase_name: "ACTIVITY",
         name: "act",
         short: "",
-        desc: "The record duration of activity in minutes"
+        desc: "The record duration of activity, for example: \"45\", \"1h30m\", \"1:30\", \"1.5h\", \"+15m\", \"-10m\""
     };
 
     pub const REST: Cmd = Cmd {
         upcase_name: "REST",
         name: "rest",
         short: "",
-        desc: "The record duration of rest in minutes"
+        desc: "The record duration of rest, for example: \"5\", \"1h30m\", \"1:30\", \"0.5h\", \"+15m\", \"-10m\""
     };
 
     pub const HOURS: Cmd = Cmd {
@@ -127,54 +413,337 @@ impl Cmd {
         desc: "All lines"
     };
 
+    pub const DRY_RUN: Cmd = Cmd {
+        upcase_name: "DRY_RUN",
+        name: "dry-run",
+        short: "d",
+        desc: "Show changes without writing them"
+    };
+
+    pub const CAP: Cmd = Cmd {
+        upcase_name: "CAP",
+        name: "cap",
+        short: "",
+        desc: "Maximum duration of activity in minutes for closed dangling records"
+    };
+
+    pub const FORCE: Cmd = Cmd {
+        upcase_name: "FORCE",
+        name: "force",
+        short: "f",
+        desc: "Apply changes even if the record overlaps other records"
+    };
+
+    pub const MATCH: Cmd = Cmd {
+        upcase_name: "MATCH",
+        name: "match",
+        short: "m",
+        desc: "Select the last record whose note matches the regular expression"
+    };
+
+    pub const ALL_MATCHES: Cmd = Cmd {
+        upcase_name: "ALL_MATCHES",
+        name: "all-matches",
+        short: "",
+        desc: "Select all records matched by --match instead of the last one"
+    };
+
+    pub const AT: Cmd = Cmd {
+        upcase_name: "AT",
+        name: "at",
+        short: "",
+        desc: "Select the record running at the moment, for example: \"14:00\", \"2018-08-25 14:00\" (quoted)"
+    };
+
+    pub const ID: Cmd = Cmd {
+        upcase_name: "ID",
+        name: "id",
+        short: "",
+        desc: "Select the record by its identifier"
+    };
+
+    pub const REPLACE: Cmd = Cmd {
+        upcase_name: "REPLACE",
+        name: "replace",
+        short: "",
+        desc: "Replace the parts of the note matched by --match, for example: \"Client work\", \"$1 done\""
+    };
+
+    pub const YES: Cmd = Cmd {
+        upcase_name: "YES",
+        name: "yes",
+        short: "y",
+        desc: "Apply changes of several records without confirmation"
+    };
+
     pub const OFFSET: Cmd = Cmd {
         upcase_name: "OFFSET",
         name: "offset",
         short: "n",
-        desc: "Backward offset from the last record"
+        desc: "Backward offset from the last record, or a range of offsets for set and del, for example: \"2\", \"0..5\""
     };
 }
 
+/// How to stop a record running longer than the idle threshold
+enum Idle {
+    /// Count all the elapsed time as activity
+    All,
+    /// Book the time over the threshold as rest
+    Rest,
+    /// Stop the record at the moment
+    At(DateTime<Local>),
+}
+
 pub struct CmdProcessor {
     journal: FileJournal,
     print: bool,
+    overlap: Overlap,
+    confirm: bool,
+    idle_threshold: Duration,
+    max_activity: Option<Duration>,
+    pomodoro_work: Duration,
+    pomodoro_break: Duration,
+    aliases: HashMap<String, String>,
+    calendar_notes: HashMap<String, String>,
+    branch_note_template: String,
 }
 
 impl CmdProcessor {
+    /// Number of the last records to collect notes from
+    const NOTES_DEPTH: usize = 500;
+    /// Number of the recent notes offered by resume
+    const RESUME_MENU_SIZE: usize = 9;
+
     pub fn new(settings: &Settings) -> Self {
         CmdProcessor {
             journal: FileJournal::new(&settings.journal_file),
             print: settings.print,
+            overlap: settings.overlap,
+            confirm: true,
+            idle_threshold: Duration::minutes(settings.idle_threshold),
+            max_activity: if settings.max_activity > 0 {
+                Some(Duration::minutes(settings.max_activity))
+            } else {
+                None
+            },
+            pomodoro_work: Duration::minutes(settings.pomodoro_work),
+            pomodoro_break: Duration::minutes(settings.pomodoro_break),
+            aliases: settings.aliases.clone(),
+            calendar_notes: settings.calendar_notes.clone(),
+            branch_note_template: settings.branch_note_template.clone(),
         }
     }
 
     pub fn start(&mut self, matches: &ArgMatches) {
+        let note = self.expand_note(Self::get_note(matches));
+        self.start_record(note);
+
+        if let Some(planned) = Self::get_interval(matches, Cmd::FOR.name) {
+            let id = self.last_record()
+                .and_then(|record| record_id(&record))
+                .expect("Can't get identifier of the started record");
+            let mut meta = self.meta();
+            meta.set(&id, Meta::PLANNED, &planned.num_minutes().to_string());
+            meta.save();
+
+            if Self::is_wait(matches) {
+                pomodoro::count_down(&id, planned);
+                let still_running = self.is_running() && self.last_record().and_then(|record| record_id(&record)) == Some(id);
+                if still_running {
+                    self.stop_record(-1, None);
+                }
+                pomodoro::ring();
+            }
+        }
+    }
+
+    pub fn status(&mut self, matches: &ArgMatches) {
+        if let Some(template) = matches.args.get(Cmd::FORMAT.name).and_then(|arg| arg.vals[0].to_str()) {
+            println!("{}", self.format_status(template));
+            return;
+        }
+        let record = match self.last_record() {
+            Some(record) if record.start.is_some() && record.activity.is_none() => record,
+            _ => {
+                println!("Nothing is running");
+                return;
+            },
+        };
+        let elapsed = Self::elapsed(&record).unwrap_or_else(Duration::zero);
+        println!("{}", record.to_string());
+        let elapsed_time = report::hours_minutes(elapsed.num_minutes());
+        match Self::planned(&self.meta(), &record) {
+            Some(planned) => println!("Elapsed {}, {}", elapsed_time, Self::plan_status(elapsed, planned)),
+            None => println!("Elapsed {}", elapsed_time),
+        }
+    }
+
+    /// Fills the placeholders of the status template, reading only the journal tail of today records
+    fn format_status(&self, template: &str) -> String {
+        let today = Local::now().date();
+        let running = self.last_record().filter(|record| record.start.is_some() && record.activity.is_none());
+        let elapsed = running.as_ref().and_then(Self::elapsed);
+        let mut total = self.records_on(today)
+            .iter()
+            .filter_map(|record| record.activity)
+            .fold(Duration::zero(), |total, activity| total + activity);
+        if running.as_ref().and_then(|record| record.start).map(|start| start.date()) == Some(today) {
+            total = total + elapsed.unwrap_or_else(Duration::zero);
+        }
+
+        let note = running.as_ref().map(|record| record.note.as_str()).unwrap_or_default();
+        template
+            .replace("{note}", note)
+            .replace("{project}", note.split_whitespace().next().unwrap_or_default())
+            .replace("{elapsed}", &elapsed.map(|elapsed| report::hours_minutes(elapsed.num_minutes())).unwrap_or_default())
+            .replace("{today_total}", &report::hours_minutes(total.num_minutes()))
+            .trim()
+            .to_string()
+    }
+
+    /// Returns the intended duration of the record given by `start --for`
+    fn planned(meta: &Meta, record: &Record) -> Option<Duration> {
+        record_id(record)
+            .and_then(|id| meta.get(&id, Meta::PLANNED))
+            .and_then(|minutes| minutes.parse::<i64>().ok())
+            .map(Duration::minutes)
+    }
+
+    fn plan_status(activity: Duration, planned: Duration) -> String {
+        let (activity, planned) = (activity.num_minutes(), planned.num_minutes());
+        if activity <= planned {
+            format!("{} left of {}", report::hours_minutes(planned - activity), report::hours_minutes(planned))
+        } else {
+            format!("{} over {}", report::hours_minutes(activity - planned), report::hours_minutes(planned))
+        }
+    }
+
+    pub fn start_record(&mut self, note: Option<String>) {
         let mut record = Record::now();
 
-        if let Some(note) = Self::get_note(matches) {
+        if let Some(note) = note {
             record.note = note;
         }
+        let index = JournalIndex::current(self.journal.path());
         self.journal.add(&record)
             .expect(&format!("Can't add new record to journal {:?}", self.journal.path()));
+        self.reindex(index, true);
         if self.print {
             println!("{}", record.to_string());
         }
     }
 
     pub fn stop(&mut self, matches: &ArgMatches) {
-        self.update(Self::get_offset(matches), |mut record| {
-            let note = Self::get_note(matches);
+        let offset = self.find_offset(&Self::get_selector(matches));
+        let note = self.expand_note(Self::get_note(matches));
+        let record = self.journal.get(&[], Some(offset))
+            .expect(&format!("Can't get record from journal {:?}", self.journal.path()));
+
+        match record.as_ref().and_then(|record| self.idle_time(record).map(|elapsed| (record, elapsed))) {
+            Some((record, elapsed)) => {
+                let idle = match Self::get_idle(matches) {
+                    Some(text) => Self::parse_idle(&text, record).unwrap_or_else(|message| {
+                        eprintln!("{}", message);
+                        process::exit(1);
+                    }),
+                    None => self.ask_idle(elapsed, record),
+                };
+                self.stop_record_as(offset, note, idle);
+            },
+            None => self.stop_record(offset, note),
+        }
+    }
+
+    pub fn stop_record(&mut self, offset: i32, note: Option<String>) {
+        self.stop_record_as(offset, note, Idle::All);
+    }
+
+    /// Returns the elapsed activity of the running record when it exceeds the idle threshold
+    fn idle_time(&self, record: &Record) -> Option<Duration> {
+        if self.idle_threshold <= Duration::zero() || record.activity.is_some() {
+            return None;
+        }
+        Self::elapsed(record).filter(|elapsed| *elapsed > self.idle_threshold)
+    }
+
+    /// Returns the activity of the running record until now
+    pub fn elapsed(record: &Record) -> Option<Duration> {
+        record.start
+            .map(|start| Local::now().signed_duration_since(start) - record.rest.unwrap_or_else(Duration::zero))
+    }
+
+    fn ask_idle(&self, elapsed: Duration, record: &Record) -> Idle {
+        println!(
+            "The record has been running for {}, longer than the idle threshold of {}",
+            report::hours_minutes(elapsed.num_minutes()), report::hours_minutes(self.idle_threshold.num_minutes())
+        );
+        loop {
+            print!("Count [a]ll of it, book the excess as [r]est or enter the stop time, for example \"17:30\": ");
+            io::stdout().flush().expect("Can't flush stdout");
+
+            let mut answer = String::new();
+            io::stdin().read_line(&mut answer).expect("Can't read answer");
+            match Self::parse_idle(answer.trim(), record) {
+                Ok(idle) => return idle,
+                Err(message) => println!("{}", message),
+            }
+        }
+    }
+
+    /// Stops the record booking the idle time as chosen, then caps its activity by max_activity
+    fn stop_record_as(&mut self, offset: i32, note: Option<String>, idle: Idle) {
+        let threshold = self.idle_threshold;
+        let max_activity = self.max_activity;
+        let mut capped = None;
+        self.update(offset, |mut record| {
             if let Some(note) = note {
                 record.note = note;
             }
             record.update_activity_to_now();
+
+            let rest = record.rest.unwrap_or_else(Duration::zero);
+            match idle {
+                Idle::All => (),
+                Idle::Rest => if let Some(act) = record.activity.filter(|act| *act > threshold) {
+                    record.rest = Some(rest + act - threshold);
+                    record.activity = Some(threshold);
+                },
+                Idle::At(end) => {
+                    let start = record.start.expect("Can't stop the record without start");
+                    record.activity = Some(end.signed_duration_since(start) - rest);
+                },
+            }
+
+            if let (Some(max), Some(act)) = (max_activity, record.activity) {
+                if act > max {
+                    record.activity = Some(max);
+                    capped = record_id(&record).map(|id| (id, act));
+                }
+            }
             record
         });
+
+        if let Some((id, act)) = capped {
+            let mut meta = self.meta();
+            meta.set(&id, Meta::CAPPED, &act.num_minutes().to_string());
+            meta.save();
+            if self.print {
+                println!(
+                    "The activity of {} minutes is capped by max_activity, see `tt list --{}`",
+                    act.num_minutes(), Cmd::FLAGGED.name
+                );
+            }
+        }
     }
 
     pub fn restart(&mut self, matches: &ArgMatches) {
-        self.update(Self::get_offset(matches), |mut record| {
-            let note = Self::get_note(matches);
+        let offset = self.find_offset(&Self::get_selector(matches));
+        let note = self.expand_note(Self::get_note(matches));
+        self.restart_record(offset, note);
+    }
+
+    pub fn restart_record(&mut self, offset: i32, note: Option<String>) {
+        self.update(offset, |mut record| {
             if let Some(note) = note {
                 record.note = note;
             }
@@ -183,16 +752,156 @@ impl CmdProcessor {
         });
     }
 
+    pub fn update_note(&mut self, offset: i32, note: String) {
+        self.update(offset, |mut record| {
+            record.note = note;
+            record
+        });
+    }
+
+    /// Returns the last record of the journal
+    pub fn last_record(&self) -> Option<Record> {
+        self.journal.get(&[], Some(-1))
+            .expect(&format!("Can't get record from journal {:?}", self.journal.path()))
+    }
+
+    /// Whether the last record is started and not stopped yet
+    pub fn is_running(&self) -> bool {
+        self.last_record()
+            .map(|record| record.start.is_some() && record.activity.is_none())
+            .unwrap_or(false)
+    }
+
+    /// Returns records started within the dates, both are optional
+    pub fn records_between(&self, from: Option<Date<Local>>, to: Option<Date<Local>>) -> Vec<Record> {
+        self.items()
+            .into_iter()
+            .filter_map(|item| match item {
+                Item::Record(record) => Some(record),
+                Item::SomeLine(_) => None,
+            })
+            .filter(|record| match record.start {
+                Some(start) => from.iter().all(|from| start.date() >= *from) && to.iter().all(|to| start.date() <= *to),
+                None => false,
+            })
+            .collect()
+    }
+
+    /// Returns records started on the date
+    pub fn records_on(&self, date: Date<Local>) -> Vec<Record> {
+        let error_message = format!("Can't list records from journal {:?}", self.journal.path());
+        let mut records = Vec::new();
+
+        let tail = JournalTail::open(self.journal.path(), Some(date));
+        let mut iter = tail.journal().try_iter().expect(&error_message);
+        iter.go_to_end();
+        while let Some(item) = iter.backward(1).get() {
+            if let Item::Record(r) = item {
+                match r.start.map(|start| start.date()) {
+                    Some(start) if start < date => break,
+                    Some(start) if start == date => records.push(r),
+                    _ => (),
+                }
+            }
+        }
+        records.reverse();
+        records
+    }
+
+    /// Returns distinct notes of the recent records, the most frequent first and then the most recent
+    pub fn frequent_notes(&self) -> Vec<String> {
+        let mut notes = self.recent_notes();
+        notes.sort_by_key(|&(_, count)| Reverse(count));
+        notes.into_iter().map(|(note, _)| note).collect()
+    }
+
+    /// Returns distinct notes of the recent records with their counts, the most recent first
+    fn recent_notes(&self) -> Vec<(String, usize)> {
+        let error_message = format!("Can't read records from journal {:?}", self.journal.path());
+        let mut notes: Vec<(String, usize)> = Vec::new();
+        let mut depth = 0;
+
+        let mut iter = self.journal.try_iter().expect(&error_message);
+        iter.go_to_end();
+        while let Some(item) = iter.backward(1).get() {
+            if let Item::Record(r) = item {
+                depth += 1;
+                if depth > Self::NOTES_DEPTH {
+                    break;
+                }
+                if r.note.trim().is_empty() {
+                    continue;
+                }
+                match notes.iter_mut().find(|(note, _)| *note == r.note) {
+                    Some((_, count)) => *count += 1,
+                    None => notes.push((r.note, 1)),
+                }
+            }
+        }
+        notes
+    }
+
+    pub fn resume(&mut self, matches: &ArgMatches) {
+        let mut notes = self.recent_notes().into_iter().map(|(note, _)| note);
+        let note = match Self::get_query(matches) {
+            Some(query) => {
+                let query = query.to_lowercase();
+                notes.find(|note| note.to_lowercase().contains(&query))
+            },
+            None => Self::choose(&notes.take(Self::RESUME_MENU_SIZE).collect::<Vec<_>>()),
+        };
+
+        match note {
+            Some(note) => {
+                if self.is_running() {
+                    self.stop_record(-1, None);
+                }
+                self.start_record(Some(note));
+            },
+            None => println!("Nothing to resume"),
+        }
+    }
+
+    pub fn pomodoro(&mut self, matches: &ArgMatches) {
+        let note = self.expand_note(Self::get_note(matches));
+        let work = Self::get_interval(matches, Cmd::WORK.name).unwrap_or(self.pomodoro_work);
+        let rest = Self::get_interval(matches, Cmd::BREAK.name).unwrap_or(self.pomodoro_break);
+        let cycles = Self::get_cycles(matches).unwrap_or(1);
+        Pomodoro::new(self, work, rest, cycles).run(note);
+    }
+
+    pub fn notes(&mut self, matches: &ArgMatches) {
+        let typed = Self::get_note(matches).unwrap_or_default();
+        let typed = typed.split_whitespace().collect::<Vec<_>>();
+        let mut words: Vec<String> = Vec::new();
+
+        for note in self.frequent_notes() {
+            let note = note.split_whitespace().collect::<Vec<_>>();
+            if note.len() > typed.len() && note.starts_with(&typed) {
+                let word = note[typed.len()].to_string();
+                if !words.contains(&word) {
+                    words.push(word);
+                }
+            }
+        }
+        words.iter().for_each(|word| println!("{}", word));
+    }
+
     pub fn list(&mut self, matches: &ArgMatches) {
-        let date = if !Self::is_all(matches) {
-            Self::get_date(matches).or(Some(Local::now().date()))
+        let flagged = Self::is_flagged(matches);
+        let date = if Self::is_all(matches) || flagged {
+            Self::get_date(matches).filter(|_| flagged)
         } else {
-            None
+            Self::get_date(matches).or(Some(Local::now().date()))
         };
+        let with_end = Self::is_with_end(matches);
+        let with_id = Self::is_with_id(matches);
+        let meta = self.meta();
         let error_message = format!("Can't list records from journal {:?}", self.journal.path());
         let mut out = Vec::new();
 
-        let mut iter = self.journal.try_iter().expect(&error_message);
+        let tail = JournalTail::open(self.journal.path(), date);
+        let mut iter = tail.journal().try_iter().expect(&error_message);
         iter.go_to_end();
         loop {
             if let Some(item) = iter.backward(1).get() {
@@ -203,8 +912,30 @@ impl CmdProcessor {
                                 break;
                             }
                         }
-                        r.to_string()
+                        let capped = record_id(&r).and_then(|id| meta.get(&id, Meta::CAPPED).map(str::to_string));
+                        if flagged && capped.is_none() {
+                            continue;
+                        }
+                        let mut line = r.to_string();
+                        if let Some(capped) = capped {
+                            line = format!("{}  (capped from {})", line, capped);
+                        }
+                        if let Some(planned) = Self::planned(&meta, &r) {
+                            let activity = r.activity.or_else(|| Self::elapsed(&r)).unwrap_or_else(Duration::zero);
+                            line = format!("{}  ({})", line, Self::plan_status(activity, planned));
+                        }
+                        if with_end {
+                            let end = Self::end_of(&r)
+                                .map(|end| end.format("%H:%M:%S").to_string())
+                                .unwrap_or_default();
+                            line = format!("{:8}  {}", end, line);
+                        }
+                        if with_id {
+                            line = format!("{:14}  {}", record_id(&r).unwrap_or_default(), line);
+                        }
+                        line
                     },
+                    Item::SomeLine(_) if flagged => continue,
                     Item::SomeLine(s) => s,
                 };
                 out.push(line);
@@ -216,18 +947,251 @@ impl CmdProcessor {
     }
 
     pub fn del(&mut self, matches: &ArgMatches) {
-        let offset = Self::get_offset(matches);
+        if Self::is_yes(matches) {
+            self.confirm = false;
+        }
+        match self.find_target(&Self::get_selector(matches)) {
+            Target::One(offset) => self.remove(offset),
+            Target::Many(ref offsets) if offsets.len() == 1 => self.remove(offsets[0]),
+            Target::Many(offsets) => self.remove_many(&offsets),
+        }
+    }
+
+    fn remove(&mut self, offset: i32) {
         let error_message = format!("Can't del record in journal {:?}", self.journal.path());
         let print = self.print;
+        let mut id = None;
 
+        let index = JournalIndex::current(self.journal.path());
         if !self.journal.remove(&[], Some(offset), |record| {
             if print {
                 println!("{}", record.to_string());
             }
+            id = record_id(record);
             true
         }).expect(&error_message) {
             panic!(error_message);
         }
+        self.reindex(index, offset == -1);
+        self.rekey_meta(id.into_iter().map(|id| (id, None)).collect());
+    }
+
+    pub fn import(&mut self, matches: &ArgMatches) {
+        let format = Self::get_format(matches);
+        let path = Self::get_file(matches);
+        let content = fs::read_to_string(&path).expect(&format!("Can't read file {:?}", path));
+        let records = import::read_records(format, &content)
+            .unwrap_or_else(|err| panic!("Can't import records from {:?}: {}", path, err));
+
+        let (added, skipped) = self.insert_records(records);
+        if self.print {
+            println!("Imported {} records, skipped {} already in the journal", added, skipped);
+        }
+    }
+
+    pub fn import_calendar(&mut self, matches: &ArgMatches) {
+        if Self::is_yes(matches) {
+            self.confirm = false;
+        }
+        let date = Self::get_date_of(matches, Cmd::DATE.name).unwrap_or_else(|| Local::now().date());
+        let path = Self::get_file(matches);
+        let content = fs::read_to_string(&path).expect(&format!("Can't read file {:?}", path));
+        let events = ical::parse_events(&content, date.succ().and_hms(0, 0, 0))
+            .unwrap_or_else(|err| panic!("Can't import events from {:?}: {}", path, err));
+
+        let mut busy = self.busy_intervals();
+        let mut accepted = Vec::new();
+        for record in import::calendar_records(events, date, &self.calendar_notes) {
+            let interval = match (record.start, Self::end_of(&record)) {
+                (Some(start), Some(end)) => start..end,
+                _ => continue,
+            };
+            if busy.iter().any(|busy| busy.start < interval.end && interval.start < busy.end) {
+                println!("Overlaps the journal: {}", record.to_string());
+                continue;
+            }
+            println!("{}", record.to_string());
+            if !self.confirm || Self::confirm("Add the record?") {
+                busy.push(interval);
+                accepted.push(record);
+            }
+        }
+
+        if accepted.is_empty() {
+            println!("No records added");
+        } else {
+            let (added, _) = self.insert_records(accepted);
+            println!("Added {} records", added);
+        }
+    }
+
+    /// Time ranges of the journal records, the running record lasts until now
+    fn busy_intervals(&self) -> Vec<Range<DateTime<Local>>> {
+        self.items()
+            .into_iter()
+            .filter_map(|item| match item {
+                Item::Record(record) => record.start.map(|start| {
+                    start..Self::end_of(&record).unwrap_or_else(Local::now)
+                }),
+                Item::SomeLine(_) => None,
+            })
+            .collect()
+    }
+
+    pub fn git_log(&mut self, matches: &ArgMatches) {
+        if Self::is_yes(matches) {
+            self.confirm = false;
+        }
+        let repo = Self::get_repo(matches);
+        let from = Self::get_date_of(matches, Cmd::DATE.name).unwrap_or_else(|| Local::now().date());
+        let to = Self::get_date_of(matches, Cmd::TO.name).unwrap_or(from);
+        let commits = git::log(&repo, from.and_hms(0, 0, 0), to.succ().and_hms(0, 0, 0))
+            .unwrap_or_else(|err| panic!("Can't read git log of {:?}: {}", repo, err));
+        if commits.is_empty() {
+            println!("No commits");
+            return;
+        }
+
+        let mut items = self.items();
+        let now = Local::now();
+        let mut summaries: BTreeMap<usize, Vec<String>> = BTreeMap::new();
+        for commit in commits {
+            println!("{} {} {}", commit.time.format("%Y-%m-%d %H:%M"), commit.hash, commit.summary);
+            let index = items.iter().position(|item| match item {
+                Item::Record(record) => match record.start {
+                    Some(start) => start <= commit.time && commit.time < Self::end_of(record).unwrap_or(now),
+                    None => false,
+                },
+                Item::SomeLine(_) => false,
+            });
+            match index {
+                Some(index) => {
+                    println!("    {}", Self::item_to_string(&items[index]));
+                    summaries.entry(index).or_default().push(commit.summary);
+                },
+                None => println!("    No running record"),
+            }
+        }
+
+        if Self::is_append(matches) {
+            let mut lines = self.lines(&items);
+            let mut changes = Vec::new();
+            for (index, summaries) in summaries {
+                if let Item::Record(record) = &mut items[index] {
+                    for summary in summaries {
+                        if !record.note.contains(summary.as_str()) {
+                            record.note = format!("{} {}", record.note, summary).trim().to_string();
+                        }
+                    }
+                    let line = record.to_string();
+                    if line != lines[index] {
+                        lines[index] = line.clone();
+                        changes.push(line);
+                    }
+                }
+            }
+            if self.confirm_changes(&changes) {
+                self.write_lines(&lines);
+            }
+        }
+    }
+
+    pub fn install_hook(&self, matches: &ArgMatches) {
+        let repo = Self::get_repo(matches);
+        let path = git::install_hook(&repo)
+            .unwrap_or_else(|err| panic!("Can't install hook to {:?}: {}", repo, err));
+        if self.print {
+            println!("Installed {:?}", path);
+        }
+    }
+
+    /// Switches tracking to the note of the checked out branch, unless its record is already running
+    pub fn checkout(&mut self, matches: &ArgMatches) {
+        let branch = matches.args
+            .get(Cmd::BRANCH.upcase_name)
+            .and_then(|arg| arg.vals[0].to_str())
+            .expect("Branch is required");
+        let note = git::branch_note(&self.branch_note_template, branch);
+
+        if self.is_running() {
+            if self.last_record().map(|record| record.note == note).unwrap_or(false) {
+                return;
+            }
+            self.stop_record(-1, None);
+        }
+        self.start_record(Some(note));
+    }
+
+    pub fn export(&self, matches: &ArgMatches) {
+        let format = matches.args
+            .get(Cmd::FORMAT.name)
+            .and_then(|arg| arg.vals[0].to_str())
+            .and_then(export::Format::parse)
+            .expect("Unknown export format");
+        let grouping = matches.args
+            .get(Cmd::BY.name)
+            .and_then(|arg| arg.vals[0].to_str())
+            .map(|name| Grouping::parse(name).expect("Unknown timesheet grouping"))
+            .unwrap_or(Grouping::Record);
+        let from = Self::get_date_of(matches, Cmd::FROM.name);
+        let to = Self::get_date_of(matches, Cmd::TO.name);
+
+        let records = self.records_between(from, to);
+        io::stdout()
+            .write_all(&export::write_records(format, grouping, &records))
+            .expect("Can't write exported records");
+    }
+
+    /// Inserts the records into the journal in chronological order, skipping the ones with the same start and note
+    /// as existing records. Returns the numbers of inserted and skipped records
+    fn insert_records(&mut self, records: Vec<Record>) -> (usize, usize) {
+        let items = self.items();
+        let mut lines = self.lines(&items);
+        let mut keys = items
+            .iter()
+            .map(|item| match item {
+                Item::Record(record) => record.start.map(|start| (start, record.note.clone())),
+                Item::SomeLine(_) => None,
+            })
+            .collect::<Vec<_>>();
+
+        let latest = records.iter().filter_map(|record| record.start).max();
+        let running = self.is_running();
+
+        let (mut added, mut skipped) = (0, 0);
+        for record in records {
+            let start = match record.start {
+                Some(start) => start,
+                None => continue,
+            };
+            let exists = keys.iter().any(|key| match key {
+                Some((key_start, key_note)) => *key_start == start && *key_note == record.note,
+                None => false,
+            });
+            if exists {
+                skipped += 1;
+                continue;
+            }
+            if record.activity.is_none() {
+                let last = Some(start) == latest && keys.iter().flatten().all(|(key_start, _)| *key_start < start);
+                if running || !last {
+                    eprintln!("Skipped the open record {}, only the last record of the journal can be running", record.to_string());
+                    continue;
+                }
+            }
+
+            let index = keys.iter()
+                .position(|key| key.as_ref().map(|(key_start, _)| *key_start > start).unwrap_or(false))
+                .unwrap_or(keys.len());
+            lines.insert(index, record.to_string());
+            keys.insert(index, Some((start, record.note)));
+            added += 1;
+        }
+
+        if added > 0 {
+            self.write_lines(&lines);
+        }
+        (added, skipped)
     }
 
     pub fn report(&mut self, matches: &ArgMatches) {
@@ -236,22 +1200,93 @@ impl CmdProcessor {
         } else {
             None
         };
+        let print_in_hours = Self::is_in_hours(matches);
+        let print_root_items_only= Self::is_root_items_only(matches);
+
+        let (nodes, total) = self.report_nodes(date, None);
+        for node in nodes.iter() {
+            println!("{}", node.to_string(print_in_hours, print_root_items_only));
+        }
+        if print_in_hours {
+            Self::print_total(report::hours_minutes(total));
+        } else {
+            Self::print_total(total);
+        }
+    }
+
+    pub fn summary(&mut self, matches: &ArgMatches) {
+        let period = if Self::is_month(matches) { Period::Month } else { Period::Week };
+        let date = Self::get_date(matches).unwrap_or_else(|| Local::now().date());
+        let (from, _) = period.previous(date);
+        let (_, to) = period.range(date);
+
+        for line in summary::summary(period, date, Local::now().date(), &self.summary_entries(from, to)) {
+            println!("{}", line);
+        }
+    }
+
+    /// Returns the activity of records started within the dates, the running record counts as in the report
+    fn summary_entries(&self, from: Date<Local>, to: Date<Local>) -> Vec<Entry> {
+        let error_message = format!("Can't summarize records from journal {:?}", self.journal.path());
+        let mut entries = Vec::new();
+
+        let mut last = true;
+        let tail = JournalTail::open(self.journal.path(), Some(from));
+        let mut iter = tail.journal().try_iter().expect(&error_message);
+        iter.go_to_end();
+        while let Some(item) = iter.backward(1).get() {
+            if let Item::Record(r) = item {
+                let running = last && r.activity.is_none();
+                last = false;
+                let start = match r.start {
+                    Some(start) if start.date() < from => break,
+                    Some(start) if start.date() <= to => start,
+                    _ => continue,
+                };
+                if let Some(act) = self.counted_activity(&r, running) {
+                    entries.push(Entry { date: start.date(), note: r.note, minutes: act.num_minutes() });
+                }
+            }
+        }
+        entries
+    }
+
+    /// Returns the activity of the record as the reports count it: the running record counts until now,
+    /// capped by `max_activity`, only when the setting is on
+    fn counted_activity(&self, record: &Record, running: bool) -> Option<Duration> {
+        match self.max_activity {
+            Some(max) if running => Self::elapsed(record).map(|act| if act > max { max } else { act }),
+            _ => record.activity,
+        }
+    }
+
+    /// Builds collapsed report trees and total activity of records started within the dates
+    pub fn report_nodes(&self, from: Option<Date<Local>>, to: Option<Date<Local>>) -> (Vec<ReportNode>, i64) {
         let error_message = format!("Can't report records from journal {:?}", self.journal.path());
         let mut collection = BTreeMap::new();
 
         let mut total = 0;
-        let mut iter = self.journal.try_iter().expect(&error_message);
+        let mut last = true;
+        let tail = JournalTail::open(self.journal.path(), from);
+        let mut iter = tail.journal().try_iter().expect(&error_message);
         iter.go_to_end();
         loop {
             if let Some(item) = iter.backward(1).get() {
                 match item {
                     Item::Record(r) => {
-                        if date.is_some() && r.start.is_some() {
-                            if r.start.unwrap().date() < date.unwrap() {
+                        let running = last && r.activity.is_none();
+                        last = false;
+                        if from.is_some() && r.start.is_some() {
+                            if r.start.unwrap().date() < from.unwrap() {
                                 break;
                             }
                         }
-                        if let Some(act) = r.activity {
+                        if let (Some(to), Some(start)) = (to, r.start) {
+                            if start.date() > to {
+                                continue;
+                            }
+                        }
+                        if let Some(act) = self.counted_activity(&r, running) {
                             let mut act = act.num_minutes();
                             total += act;
 
@@ -269,9 +1304,7 @@ impl CmdProcessor {
             }
         }
 
-        let print_in_hours = Self::is_in_hours(matches);
-        let print_root_items_only= Self::is_root_items_only(matches);
-
+        let mut nodes = Vec::new();
         let mut last_node = Option::None::<ReportNode>;
         for (k, &v) in collection.iter() {
             let mut words = k.as_str().split_whitespace().collect::<Vec<&str>>();
@@ -286,7 +1319,7 @@ impl CmdProcessor {
                         Some(node)
                     } else {
                         node.collapse();
-                        println!("{}", node.to_string(print_in_hours, print_root_items_only));
+                        nodes.push(node);
                         None
                     }
                 )
@@ -295,36 +1328,278 @@ impl CmdProcessor {
 
         if let Some(mut node) = last_node {
             node.collapse();
-            println!("{}", node.to_string(print_in_hours, print_root_items_only));
+            nodes.push(node);
         }
-        if print_in_hours {
-            Self::print_total(format!("{}:{:02}", total / 60, total % 60));
+        (nodes, total)
+    }
+
+    pub fn fix(&mut self, matches: &ArgMatches) {
+        let cap = Self::get_cap(matches).map(Duration::minutes).or(self.max_activity);
+        let items = self.items();
+        let old_lines = self.lines(&items);
+        let activities = items.iter()
+            .filter_map(|item| match item {
+                Item::Record(record) => record_id(record).map(|id| (id, record.activity)),
+                Item::SomeLine(_) => None,
+            })
+            .collect::<BTreeMap<_, _>>();
+        let new_items = JournalFixer::new(cap).fix(items);
+        let new_lines = new_items.iter().map(Self::item_to_string).collect::<Vec<_>>();
+
+        let records = new_items.iter()
+            .filter_map(|item| match item {
+                Item::Record(record) => Some(record),
+                Item::SomeLine(_) => None,
+            })
+            .collect::<Vec<_>>();
+        let open = records.iter().rev().skip(1).filter(|record| record.activity.is_none()).collect::<Vec<_>>();
+        for record in open.iter().rev() {
+            eprintln!("The record is left open as the next one starts on a later day: {}", record.to_string());
+        }
+        if !open.is_empty() {
+            eprintln!("Give --{} or set max_activity to close it", Cmd::CAP.name);
+        }
+
+        let dry_run = Self::is_dry_run(matches);
+        let mut changed = false;
+        for (number, (old, new)) in old_lines.iter().zip(new_lines.iter()).enumerate() {
+            if old != new {
+                changed = true;
+                if dry_run || self.print {
+                    println!("@@ {}\n- {}\n+ {}", number + 1, old, new);
+                }
+            }
+        }
+
+        if !changed {
+            if dry_run || self.print {
+                println!("Nothing to fix");
+            }
+        } else if !dry_run {
+            self.write_lines(&new_lines);
+            let trimmed = new_items.iter()
+                .filter_map(|item| match item {
+                    Item::Record(record) => record_id(record).filter(|id| activities.get(id) != Some(&record.activity)),
+                    Item::SomeLine(_) => None,
+                })
+                .collect::<Vec<_>>();
+            self.unflag(&trimmed);
+        }
+    }
+
+    pub fn edit(&mut self, matches: &ArgMatches) {
+        if Self::is_force(matches) {
+            self.overlap = Overlap::Allow;
+        }
+        let selected_by = [Cmd::OFFSET.name, Cmd::DATE.name, Cmd::MATCH.name];
+        let selector = if selected_by.iter().all(|name| matches.occurrences_of(name) == 0) {
+            Selector::Date(Local::now().date())
         } else {
-            Self::print_total(total);
+            Self::get_selector(matches)
+        };
+        let items = self.items();
+        let offsets = if selector.is_range() {
+            selector.select(&items)
+        } else {
+            selector.select(&items).pop().into_iter().collect()
+        };
+        if offsets.is_empty() {
+            println!("No records selected");
+            return;
         }
+
+        let mut numbers = Vec::new();
+        let mut intervals = Vec::new();
+        let mut offset = 0;
+        for (number, item) in items.iter().enumerate() {
+            if let Item::Record(record) = item {
+                if offsets.contains(&offset) {
+                    numbers.push(number);
+                    intervals.push((record.start, Self::end_of(record)));
+                }
+                offset += 1;
+            }
+        }
+
+        let lines = self.lines(&items);
+        let selected = numbers.iter().map(|&number| lines[number].clone()).collect::<Vec<_>>();
+        let mut edited = edit_records(&selected).unwrap_or_else(|message| panic!("The edit is rejected: {}", message));
+
+        if self.overlap != Overlap::Allow {
+            let others = self.records_except(&offsets);
+            for (index, record) in edited.iter().enumerate() {
+                if !intervals.contains(&(record.start, Self::end_of(record))) {
+                    let edited_others = edited.iter()
+                        .enumerate()
+                        .filter(|(other, _)| *other != index)
+                        .map(|(_, other)| other);
+                    self.check_overlap(record, others.iter().chain(edited_others));
+                }
+            }
+        }
+
+        let new_ids = edited.iter().map(record_id).collect::<Vec<_>>();
+        let mut rekeyed = Vec::new();
+        for (index, &number) in numbers.iter().enumerate() {
+            if let Item::Record(record) = &items[number] {
+                if let Some(id) = record_id(record) {
+                    let new_id = if new_ids.len() == numbers.len() {
+                        new_ids[index].clone()
+                    } else {
+                        Some(id.clone()).filter(|id| new_ids.contains(&Some(id.clone())))
+                    };
+                    rekeyed.push((id, new_id));
+                }
+            }
+        }
+
+        let new_lines = Self::replace_lines(&items, lines, &numbers, &mut edited);
+        self.write_lines(&new_lines);
+        self.rekey_meta(rekeyed);
+        if self.print {
+            edited.iter().for_each(|record| println!("{}", record.to_string()));
+        }
+    }
+
+    /// Puts the edited records in place of the lines with the numbers, one for one while they stay in chronological
+    /// order, otherwise each record goes before the first record started later
+    fn replace_lines(items: &[Item], mut lines: Vec<String>, numbers: &[usize], edited: &mut [Record]) -> Vec<String> {
+        edited.sort_by_key(|record| record.start);
+        let mut starts = items
+            .iter()
+            .map(|item| match item {
+                Item::Record(record) => record.start,
+                Item::SomeLine(_) => None,
+            })
+            .collect::<Vec<_>>();
+
+        if edited.len() == numbers.len() {
+            for (&number, record) in numbers.iter().zip(edited.iter()) {
+                starts[number] = record.start;
+            }
+            let in_order = numbers.iter().all(|&number| {
+                let before = starts[..number].iter().rev().find_map(|start| *start);
+                let after = starts[number + 1..].iter().find_map(|start| *start);
+                before.iter().all(|before| Some(*before) <= starts[number])
+                    && after.iter().all(|after| starts[number] <= Some(*after))
+            });
+            if in_order {
+                for (&number, record) in numbers.iter().zip(edited.iter()) {
+                    lines[number] = record.to_string();
+                }
+                return lines;
+            }
+        }
+
+        let mut kept = lines.into_iter()
+            .zip(starts)
+            .enumerate()
+            .filter(|(number, _)| !numbers.contains(number))
+            .map(|(_, line)| line)
+            .collect::<Vec<_>>();
+        for record in edited.iter() {
+            let index = kept.iter()
+                .position(|(_, start)| start.map(|start| Some(start) > record.start).unwrap_or(false))
+                .unwrap_or(kept.len());
+            kept.insert(index, (record.to_string(), record.start));
+        }
+        kept.into_iter().map(|(line, _)| line).collect()
+    }
+
+    pub fn set(&mut self, matches: &ArgMatches) {
+        if Self::is_force(matches) {
+            self.overlap = Overlap::Allow;
+        }
+        let note_matches = matches.subcommand_matches(Cmd::NOTE.name);
+        if Self::is_yes(matches) || note_matches.map(Self::is_yes).unwrap_or(false) {
+            self.confirm = false;
+        }
+        let selector = match note_matches {
+            Some(note_matches) if note_matches.occurrences_of(Cmd::MATCH.name) > 0 => Self::get_selector(note_matches),
+            _ => Self::get_selector(matches),
+        };
+        let target = self.find_target(&selector);
+
+        let mut activity_changed = false;
+        if let Some(matches) = note_matches {
+            self.set_note(matches, &target, &selector);
+        } else if let Some(matches) = matches.subcommand_matches(Cmd::DATE.name) {
+            self.set_date(matches, &target);
+        } else if let Some(matches) = matches.subcommand_matches(Cmd::TIME.name) {
+            self.set_time(matches, &target);
+        } else if let Some(matches) = matches.subcommand_matches(Cmd::DATETIME.name) {
+            self.set_datetime(matches, &target);
+        } else if let Some(matches) = matches.subcommand_matches(Cmd::END.name) {
+            activity_changed = self.set_end(matches, &target);
+        } else if let Some(matches) = matches.subcommand_matches(Cmd::ACTIVITY.name) {
+            activity_changed = self.set_act(matches, &target);
+        } else if let Some(matches) = matches.subcommand_matches(Cmd::REST.name) {
+            activity_changed = self.set_rest(matches, &target);
+        }
+        // The activity written by hand is not capped anymore
+        if activity_changed {
+            let ids = self.target_ids(&target);
+            self.unflag(&ids);
+        }
+    }
+
+    /// Returns identifiers of the target records
+    fn target_ids(&self, target: &Target) -> Vec<String> {
+        let offsets = match target {
+            Target::One(offset) => vec![*offset],
+            Target::Many(offsets) => offsets.clone(),
+        };
+        offsets
+            .iter()
+            .filter_map(|offset| self.journal.get(&[], Some(*offset))
+                .expect(&format!("Can't get record from journal {:?}", self.journal.path())))
+            .filter_map(|record| record_id(&record))
+            .collect()
+    }
+
+    /// Removes the capped activity flag from the records
+    fn unflag(&self, ids: &[String]) {
+        let mut meta = self.meta();
+        let mut changed = false;
+        for id in ids {
+            changed |= meta.remove(id, Meta::CAPPED);
+        }
+        if changed {
+            meta.save();
+        }
+    }
+
+    /// Moves the record attributes after the records changed their starts or were removed, see `Meta::rekey`
+    fn rekey_meta(&self, changes: Vec<(String, Option<String>)>) {
+        if changes.iter().all(|(id, new_id)| new_id.as_ref() == Some(id)) {
+            return;
+        }
+        let mut meta = self.meta();
+        if meta.rekey(&changes) {
+            meta.save();
+        }
+    }
+
+    fn meta(&self) -> Meta {
+        Meta::load(self.journal.path())
     }
 
-    pub fn set(&mut self, matches: &ArgMatches) {
-        let offset = Self::get_offset(matches);
-        if let Some(matches) = matches.subcommand_matches(Cmd::NOTE.name) {
-            self.set_note(matches, offset);
-        } else if let Some(matches) = matches.subcommand_matches(Cmd::DATE.name) {
-            self.set_date(matches, offset);
-        } else if let Some(matches) = matches.subcommand_matches(Cmd::TIME.name) {
-            self.set_time(matches, offset);
-        } else if let Some(matches) = matches.subcommand_matches(Cmd::DATETIME.name) {
-            self.set_datetime(matches, offset);
-        } else if let Some(matches) = matches.subcommand_matches(Cmd::ACTIVITY.name) {
-            self.set_act(matches, offset);
-        } else if let Some(matches) = matches.subcommand_matches(Cmd::REST.name) {
-            self.set_rest(matches, offset);
+    fn set_note(&mut self, matches: &ArgMatches, target: &Target, selector: &Selector) {
+        let replace = Self::get_replace(matches);
+        let regex = match selector {
+            Selector::Match(regex) | Selector::Matches(regex) => Some(regex),
+            _ => None,
+        };
+        if replace.is_some() && regex.is_none() {
+            panic!("Option --{} requires --{}", Cmd::REPLACE.name, Cmd::MATCH.name);
         }
-    }
+        let note = self.expand_note(Self::get_note(matches));
 
-    fn set_note(&mut self, matches: &ArgMatches, offset: i32) {
-        self.update(offset, |mut record| {
-            if let Some(note) = Self::get_note(matches) {
-                record.note = note;
+        self.update_target(target, |mut record| {
+            if let (Some(replace), Some(regex)) = (replace.as_ref(), regex) {
+                record.note = regex.replace_all(&record.note, replace.as_str()).into_owned();
+            } else if let Some(ref note) = note {
+                record.note = note.clone();
             } else {
                 record.note.clear();
             }
@@ -332,8 +1607,8 @@ impl CmdProcessor {
         });
     }
 
-    fn set_date(&mut self, matches: &ArgMatches, offset: i32) {
-        self.update(offset, |mut record| {
+    fn set_date(&mut self, matches: &ArgMatches, target: &Target) {
+        self.update_target(target, |mut record| {
             if let Some(date) = Self::get_date(matches) {
                 let hour = record.start.map(|dt| dt.hour()).unwrap_or(0);
                 let min = record.start.map(|dt| dt.minute()).unwrap_or(0);
@@ -344,17 +1619,19 @@ impl CmdProcessor {
         });
     }
 
-    fn set_time(&mut self, matches: &ArgMatches, offset: i32) {
-        self.update(offset, |mut record| {
-            if let Some(datetime) = Self::get_time(matches, record.start.clone()) {
+    fn set_time(&mut self, matches: &ArgMatches, target: &Target) {
+        self.update_target(target, |mut record| {
+            if let Some(shift) = Self::get_time_shift(matches) {
+                record.start = record.start.map(|start| start + shift);
+            } else if let Some(datetime) = Self::get_time(matches, record.start.clone()) {
                 record.start = Some(datetime);
             }
             record
         });
     }
 
-    fn set_datetime(&mut self, matches: &ArgMatches, offset: i32) {
-        self.update(offset, |mut record| {
+    fn set_datetime(&mut self, matches: &ArgMatches, target: &Target) {
+        self.update_target(target, |mut record| {
             if let Some(datetime) = Self::get_datetime(matches, record.start.clone()) {
                 record.start = Some(datetime);
             }
@@ -362,22 +1639,36 @@ impl CmdProcessor {
         });
     }
 
-    fn set_act(&mut self, matches: &ArgMatches, offset: i32) {
-        self.update(offset, |mut record| {
-            if let Some(act) =  Self::get_act(matches) {
-                record.activity = Some(Duration::minutes(act));
+    fn set_end(&mut self, matches: &ArgMatches, target: &Target) -> bool {
+        self.update_target(target, |mut record| {
+            if let Some(end) = Self::get_end(matches, record.start) {
+                let start = record.start.expect("Can't set end of the record without start");
+                let act = end.signed_duration_since(start) - record.rest.unwrap_or_else(Duration::zero);
+                if act < Duration::zero() {
+                    panic!("The end {} is earlier than the record start with rest", end.format(Record::START_DATETIME_FORMAT));
+                }
+                record.activity = Some(act);
             }
             record
-        });
+        })
     }
 
-    fn set_rest(&mut self, matches: &ArgMatches, offset: i32) {
-        self.update(offset, |mut record| {
-            if let Some(rest) =  Self::get_rest(matches) {
-                record.rest = Some(Duration::minutes(rest));
+    fn set_act(&mut self, matches: &ArgMatches, target: &Target) -> bool {
+        self.update_target(target, |mut record| {
+            if let Some(act) = Self::get_act(matches) {
+                record.activity = Some(act.apply(record.activity));
             }
             record
-        });
+        })
+    }
+
+    fn set_rest(&mut self, matches: &ArgMatches, target: &Target) -> bool {
+        self.update_target(target, |mut record| {
+            if let Some(rest) = Self::get_rest(matches) {
+                record.rest = Some(rest.apply(record.rest));
+            }
+            record
+        })
     }
 
     fn update<F>(&mut self, offset: i32, f: F)
@@ -385,9 +1676,22 @@ impl CmdProcessor {
     {
         let error_message = format!("Can't update record in journal {:?}", self.journal.path());
         let print = self.print;
+        let record = self.journal.get(&[], Some(offset))
+            .expect(&error_message)
+            .unwrap_or_else(|| panic!("{}", error_message));
+        let id = record_id(&record);
+        let (start, end) = (record.start, Self::end_of(&record));
+        let record = f(record);
+        // Stopping the running record and restarting the stopped one don't move the record over others
+        let new_end = Self::end_of(&record);
+        let moved = record.start != start || (end.is_some() && new_end.is_some() && new_end != end);
+        if moved && self.overlap != Overlap::Allow {
+            self.check_overlap(&record, &self.records_except(&[offset]));
+        }
+        let new_id = record_id(&record);
 
-        if !self.journal.update(&[], Some(offset), |record| {
-            let record = f(record);
+        let index = JournalIndex::current(self.journal.path());
+        if !self.journal.update(&[], Some(offset), |_| {
             if print {
                 println!("{}", record.to_string());
             }
@@ -395,6 +1699,304 @@ impl CmdProcessor {
         }).expect(&error_message) {
             panic!(error_message);
         }
+        self.reindex(index, offset == -1);
+        self.rekey_meta(id.into_iter().map(|id| (id, new_id.clone())).collect());
+    }
+
+    /// Updates the target records, returns false when the changes were not confirmed
+    fn update_target<F>(&mut self, target: &Target, f: F) -> bool
+        where F: Fn(Record) -> Record,
+    {
+        match target {
+            Target::One(offset) => self.update(*offset, f),
+            Target::Many(offsets) if offsets.len() == 1 => self.update(offsets[0], f),
+            Target::Many(offsets) => return self.update_many(offsets, f),
+        }
+        true
+    }
+
+    fn update_many<F>(&mut self, offsets: &[i32], f: F) -> bool
+        where F: Fn(Record) -> Record,
+    {
+        let items = self.items();
+        let mut lines = self.lines(&items);
+        let mut changes = Vec::new();
+        let mut updated = Vec::new();
+        let mut ids = Vec::new();
+
+        let mut offset = 0;
+        for (number, item) in items.into_iter().enumerate() {
+            if let Item::Record(record) = item {
+                if offsets.contains(&offset) {
+                    let interval = (record.start, Self::end_of(&record));
+                    let id = record_id(&record);
+                    let record = f(record);
+                    ids.extend(id.map(|id| (id, record_id(&record))));
+                    let line = record.to_string();
+                    changes.push(format!("- {}\n+ {}", lines[number], line));
+                    lines[number] = line;
+                    updated.push((interval != (record.start, Self::end_of(&record)), record));
+                }
+                offset += 1;
+            }
+        }
+
+        if self.overlap != Overlap::Allow {
+            let others = self.records_except(offsets);
+            for (index, (moved, record)) in updated.iter().enumerate() {
+                if *moved {
+                    let updated_others = updated.iter()
+                        .enumerate()
+                        .filter(|(other, _)| *other != index)
+                        .map(|(_, (_, other))| other);
+                    self.check_overlap(record, others.iter().chain(updated_others));
+                }
+            }
+        }
+
+        let confirmed = self.confirm_changes(&changes);
+        if confirmed {
+            self.write_lines(&lines);
+            self.rekey_meta(ids);
+        }
+        confirmed
+    }
+
+    fn remove_many(&mut self, offsets: &[i32]) {
+        let items = self.items();
+        let mut lines = Vec::new();
+        let mut changes = Vec::new();
+        let mut removed = Vec::new();
+
+        let mut offset = 0;
+        for (item, line) in items.iter().zip(self.lines(&items)) {
+            match item {
+                Item::Record(record) => {
+                    if offsets.contains(&offset) {
+                        changes.push(format!("- {}", line));
+                        removed.extend(record_id(record).map(|id| (id, None)));
+                    } else {
+                        lines.push(line);
+                    }
+                    offset += 1;
+                },
+                Item::SomeLine(_) => lines.push(line),
+            }
+        }
+
+        if self.confirm_changes(&changes) {
+            self.write_lines(&lines);
+            self.rekey_meta(removed);
+        }
+    }
+
+    /// Shows the changes and asks whether to apply them, unless confirmation is disabled
+    fn confirm_changes(&self, changes: &[String]) -> bool {
+        if changes.is_empty() {
+            println!("No records selected");
+            return false;
+        }
+        changes.iter().for_each(|change| println!("{}", change));
+        !self.confirm || Self::confirm(&format!("Apply changes to {} records?", changes.len()))
+    }
+
+    /// Shows the numbered menu of the items and returns the one chosen by the user
+    fn choose(items: &[String]) -> Option<String> {
+        if items.is_empty() {
+            return None;
+        }
+        items.iter().enumerate().for_each(|(number, item)| println!("{:2}. {}", number + 1, item));
+        print!("Choose [1-{}]: ", items.len());
+        io::stdout().flush().expect("Can't flush stdout");
+
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer).expect("Can't read answer");
+        answer.trim()
+            .parse::<usize>()
+            .ok()
+            .and_then(|number| items.get(number.wrapping_sub(1)))
+            .cloned()
+    }
+
+    fn confirm(question: &str) -> bool {
+        print!("{} [y/N] ", question);
+        io::stdout().flush().expect("Can't flush stdout");
+
+        let mut answer = String::new();
+        io::stdin().read_line(&mut answer).expect("Can't read answer");
+        match answer.trim().to_lowercase().as_str() {
+            "y" | "yes" => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the journal records except the ones at the offsets, counted from the first record or back from the end
+    fn records_except(&self, offsets: &[i32]) -> Vec<Record> {
+        let records = self.items()
+            .into_iter()
+            .filter_map(|item| match item {
+                Item::Record(record) => Some(record),
+                Item::SomeLine(_) => None,
+            })
+            .collect::<Vec<_>>();
+        let count = records.len() as i32;
+        let indexes = offsets.iter()
+            .map(|&offset| if offset < 0 { count + offset } else { offset })
+            .collect::<Vec<_>>();
+        records.into_iter()
+            .enumerate()
+            .filter(|(index, _)| !indexes.contains(&(*index as i32)))
+            .map(|(_, record)| record)
+            .collect()
+    }
+
+    /// Refuses or warns, as the overlap setting says, when the record runs at the same time as one of the others.
+    /// Running records occupy only their start moment
+    fn check_overlap<'a, I: IntoIterator<Item = &'a Record>>(&self, record: &Record, others: I) {
+        let interval = |record: &Record| record.start.map(|start| start..Self::end_of(record).unwrap_or(start));
+        let range = match interval(record) {
+            Some(range) => range,
+            None => return,
+        };
+        let overlapped = others.into_iter().find(|other| match interval(other) {
+            Some(other) => other.start < range.end && range.start < other.end,
+            None => false,
+        });
+
+        if let Some(other) = overlapped {
+            let message = format!("Record {} overlaps record {}", record.to_string(), other.to_string());
+            match self.overlap {
+                Overlap::Refuse => {
+                    eprintln!("{}, use --{} to apply anyway", message, Cmd::FORCE.name);
+                    process::exit(1);
+                },
+                Overlap::Warn => eprintln!("Warning: {}", message),
+                Overlap::Allow => (),
+            }
+        }
+    }
+
+    /// The record end: start plus durations of activity and rest
+    pub fn end_of(record: &Record) -> Option<DateTime<Local>> {
+        record.start.and_then(|start| record.activity.map(|act| {
+            start + act + record.rest.unwrap_or_else(Duration::zero)
+        }))
+    }
+
+    fn items(&self) -> Vec<Item> {
+        let error_message = format!("Can't read records from journal {:?}", self.journal.path());
+        let mut items = Vec::new();
+
+        let mut iter = self.journal.try_iter().expect(&error_message);
+        iter.go_to_end();
+        while let Some(item) = iter.backward(1).get() {
+            items.push(item);
+        }
+        items.reverse();
+        items
+    }
+
+    /// Returns the journal lines as they are in the file, or restored from the items when they can't be matched
+    fn lines(&self, items: &[Item]) -> Vec<String> {
+        match fs::read_to_string(self.journal.path()) {
+            Ok(ref content) if content.lines().count() == items.len() => content
+                .lines()
+                .map(|line| line.to_string())
+                .collect(),
+            _ => items.iter().map(Self::item_to_string).collect(),
+        }
+    }
+
+    fn write_lines(&self, lines: &[String]) {
+        let mut content = lines.join("\n");
+        if !content.is_empty() {
+            content.push('\n');
+        }
+        fs::write(self.journal.path(), content)
+            .expect(&format!("Can't write journal {:?}", self.journal.path()));
+        JournalIndex::update(self.journal.path(), lines);
+    }
+
+    /// Keeps the index taken before a write through the journal in step with it: when the write changed only the last
+    /// record, the journal is read from the last run of dates, otherwise whole. Without an index there is nothing to keep
+    fn reindex(&self, index: Option<JournalIndex>, last: bool) {
+        match index {
+            Some(index) if last => index.update_last_run(self.journal.path()),
+            Some(_) => {
+                JournalIndex::load(self.journal.path());
+            },
+            None => (),
+        }
+    }
+
+    fn item_to_string(item: &Item) -> String {
+        match item {
+            Item::Record(r) => r.to_string(),
+            Item::SomeLine(s) => s.clone(),
+        }
+    }
+
+    /// Resolves record selection to the journal offset of a single record
+    fn find_offset(&self, selector: &Selector) -> i32 {
+        match selector {
+            Selector::Offset(offset) => *offset,
+            Selector::Id(_) => {
+                let mut offsets = selector.select(&self.items());
+                if offsets.len() > 1 {
+                    eprintln!(
+                        "The {} is shared by {} records started at the same second, select the record by offset",
+                        selector, offsets.len()
+                    );
+                    process::exit(1);
+                }
+                offsets.pop()
+                    .unwrap_or_else(|| panic!("Can't find record by {} in journal {:?}", selector, self.journal.path()))
+            },
+            selector => selector
+                .find(&self.items())
+                .unwrap_or_else(|| panic!("Can't find record by {} in journal {:?}", selector, self.journal.path())),
+        }
+    }
+
+    fn find_target(&self, selector: &Selector) -> Target {
+        if selector.is_range() {
+            Target::Many(selector.select(&self.items()))
+        } else {
+            Target::One(self.find_offset(selector))
+        }
+    }
+
+    fn get_selector(matches: &ArgMatches) -> Selector {
+        let values = |name: &str| matches.args
+            .get(name)
+            .map(|arg|
+                arg.vals
+                    .iter()
+                    .map(|val|
+                        val.clone().into_string().expect(&format!("Can't convert {} {:?} to UTF-8 string", name, val))
+                    )
+                    .collect::<Vec<_>>()
+            );
+
+        if let Some(id) = values(Cmd::ID.name) {
+            Selector::Id(id[0].clone())
+        } else if let Some(at) = values(Cmd::AT.name) {
+            Selector::At(Self::parse_moment(&at[0]))
+        } else if let Some(pattern) = values(Cmd::MATCH.name) {
+            let regex = Regex::new(&pattern[0])
+                .unwrap_or_else(|err| panic!("Can't convert match {:?} to regex: {}", pattern[0], err));
+            if matches.occurrences_of(Cmd::ALL_MATCHES.name) > 0 {
+                Selector::Matches(regex)
+            } else {
+                Selector::Match(regex)
+            }
+        } else if let Some(date) = values(Cmd::DATE.name) {
+            Selector::Date(Self::parse_date(&date[0]))
+        } else if let Some(range) = values(Cmd::OFFSET.name).filter(|offset| offset[0].contains("..")) {
+            Selector::Range(Self::parse_range(&range[0]))
+        } else {
+            Selector::Offset(Self::get_offset(matches))
+        }
     }
 
     fn get_offset(matches: &ArgMatches) -> i32 {
@@ -412,6 +2014,134 @@ impl CmdProcessor {
             .unwrap_or(-1)
     }
 
+    /// Expands the note alias from the settings
+    fn expand_note(&self, note: Option<String>) -> Option<String> {
+        note.map(|note| alias::expand(&self.aliases, &note, Local::now()).unwrap_or_else(|err| panic!("{}", err)))
+    }
+
+    fn get_format(matches: &ArgMatches) -> Format {
+        matches.args
+            .get(Cmd::FROM.name)
+            .and_then(|arg| arg.vals[0].to_str())
+            .and_then(Format::parse)
+            .expect("Unknown import format")
+    }
+
+    fn get_repo(matches: &ArgMatches) -> String {
+        matches.args
+            .get(Cmd::REPO.name)
+            .map(|arg|
+                arg.vals[0]
+                    .clone()
+                    .into_string()
+                    .expect(&format!("Can't convert repository path {:?} to UTF-8 string", arg.vals[0]))
+            )
+            .unwrap_or_else(|| ".".to_string())
+    }
+
+    fn get_file(matches: &ArgMatches) -> String {
+        matches.args
+            .get(Cmd::FILE.upcase_name)
+            .map(|arg|
+                arg.vals[0]
+                    .clone()
+                    .into_string()
+                    .expect(&format!("Can't convert file {:?} to UTF-8 string", arg.vals[0]))
+            )
+            .expect("File is required")
+    }
+
+    fn get_interval(matches: &ArgMatches, name: &str) -> Option<Duration> {
+        matches.args
+            .get(name)
+            .map(|arg| {
+                let text = arg.vals[0]
+                    .clone()
+                    .into_string()
+                    .expect(&format!("Can't convert {} {:?} to UTF-8 string", name, arg.vals[0]));
+                parse_duration(&text).unwrap_or_else(|err| panic!("{}", err))
+            })
+    }
+
+    fn get_cycles(matches: &ArgMatches) -> Option<u32> {
+        matches.args
+            .get(Cmd::CYCLES.name)
+            .map(|arg|
+                arg.vals[0]
+                    .clone()
+                    .into_string()
+                    .expect(&format!("Can't convert cycles {:?} to UTF-8 string", arg.vals[0]))
+                    .parse::<u32>()
+                    .expect(&format!("Can't convert cycles {:?} to u32 number", arg.vals[0]))
+            )
+    }
+
+    fn get_idle(matches: &ArgMatches) -> Option<String> {
+        matches.args
+            .get(Cmd::IDLE.name)
+            .map(|arg|
+                arg.vals[0]
+                    .clone()
+                    .into_string()
+                    .expect(&format!("Can't convert idle {:?} to UTF-8 string", arg.vals[0]))
+            )
+    }
+
+    /// Parses "all", "rest" or the stop time relative to the record start, empty means all.
+    /// The time of the day earlier than the start is taken on the next day, after the record has run over midnight.
+    /// The stop time should be between the start with rest and now
+    fn parse_idle(text: &str, record: &Record) -> Result<Idle, String> {
+        let start = record.start;
+        let parts = text.split_whitespace().collect::<Vec<_>>();
+        let end = match parts.as_slice() {
+            [] | ["a"] | ["all"] => return Ok(Idle::All),
+            ["r"] | ["rest"] => return Ok(Idle::Rest),
+            [time] if Self::is_time(time) => {
+                let end = Self::parse_time(time, start);
+                match start {
+                    Some(start) if end < start => end + Duration::days(1),
+                    _ => end,
+                }
+            },
+            [date, time] if Self::is_time(time) => Self::parse_datetime(date, time, start),
+            _ => return Err(format!("Can't convert idle {:?} to all, rest or the stop time", text)),
+        };
+
+        let rest = record.rest.unwrap_or_else(Duration::zero);
+        match start {
+            Some(start) if end - rest < start => Err(format!(
+                "The stop {} is earlier than the record start {} with rest",
+                end.format(Record::START_DATETIME_FORMAT), start.format(Record::START_DATETIME_FORMAT)
+            )),
+            _ if end > Local::now() => Err(format!("The stop {} is in the future", end.format(Record::START_DATETIME_FORMAT))),
+            _ => Ok(Idle::At(end)),
+        }
+    }
+
+    /// Whether the text is the time of the day as "17:30" or "17:30:00"
+    fn is_time(text: &str) -> bool {
+        let parts = text.split(':').map(|part| part.parse::<u32>().ok()).collect::<Vec<_>>();
+        match parts.as_slice() {
+            [Some(hour), Some(minute)] => *hour < 24 && *minute < 60,
+            [Some(hour), Some(minute), Some(second)] => *hour < 24 && *minute < 60 && *second < 60,
+            _ => false,
+        }
+    }
+
+    fn get_query(matches: &ArgMatches) -> Option<String> {
+        matches.args
+            .get(Cmd::QUERY.upcase_name)
+            .map(|arg|
+                arg.vals
+                    .iter()
+                    .map(|val|
+                        val.clone().into_string().expect(&format!("Can't convert query {:?} to UTF-8 string", arg.vals))
+                    )
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            )
+    }
+
     fn get_note(matches: &ArgMatches) -> Option<String> {
         matches.args
             .get(Cmd::NOTE.upcase_name)
@@ -438,6 +2168,33 @@ impl CmdProcessor {
             ))
     }
 
+    fn get_date_of(matches: &ArgMatches, name: &str) -> Option<Date<Local>> {
+        matches.args
+            .get(name)
+            .map(|arg| Self::parse_date(
+                arg.vals[0]
+                    .clone()
+                    .into_string()
+                    .expect(&format!("Can't convert date {:?} to UTF-8 string", arg.vals[0]))
+                    .as_str()
+            ))
+    }
+
+    fn get_time_shift(matches: &ArgMatches) -> Option<Duration> {
+        matches.args
+            .get(Cmd::TIME.upcase_name)
+            .and_then(|arg| {
+                let text = arg.vals[0]
+                    .clone()
+                    .into_string()
+                    .expect(&format!("Can't convert time {:?} to UTF-8 string", arg.vals[0]));
+                match DurationArg::parse(&text) {
+                    Ok(DurationArg::Add(shift)) => Some(shift),
+                    _ => None,
+                }
+            })
+    }
+
     fn get_time(matches: &ArgMatches, initial: Option<DateTime<Local>>) -> Option<DateTime<Local>> {
         matches.args
             .get(Cmd::TIME.upcase_name)
@@ -467,23 +2224,81 @@ impl CmdProcessor {
                 if pair.len() != 2 {
                     panic!("Can't convert set {:?} to DateTime<Local>", pair);
                 }
-                let time = Self::parse_time(&pair[1], initial);
-                Self::parse_date(&pair[0]).and_hms(time.hour(), time.minute(), time.second())
+                Self::parse_datetime(&pair[0], &pair[1], initial)
+            })
+    }
+
+    fn get_end(matches: &ArgMatches, initial: Option<DateTime<Local>>) -> Option<DateTime<Local>> {
+        matches.args
+            .get(Cmd::END.upcase_name)
+            .map(|arg| {
+                let items = arg.vals
+                    .iter()
+                    .map(|val|
+                        val.clone()
+                            .into_string()
+                            .expect(&format!("Can't convert end {:?} to UTF-8 string", arg.vals))
+                    )
+                    .collect::<Vec<_>>();
+
+                match items.len() {
+                    1 => Self::parse_time(&items[0], initial),
+                    2 => Self::parse_datetime(&items[0], &items[1], initial),
+                    _ => panic!("Can't convert end {:?} to DateTime<Local>", items),
+                }
             })
     }
 
-    fn parse_date(text: &str) -> Date<Local> {
+    fn parse_datetime(date: &str, time: &str, initial: Option<DateTime<Local>>) -> DateTime<Local> {
+        let time = Self::parse_time(time, initial);
+        Self::parse_date(date).and_hms(time.hour(), time.minute(), time.second())
+    }
+
+    /// Parses "0..5", "0..=5", "..5" or "3.." ranges of backward offsets
+    fn parse_range(text: &str) -> Range<i32> {
+        let parse = |s: &str, default: i32| if s.is_empty() {
+            default
+        } else {
+            s.parse::<i32>().expect(&format!("Can't convert part of range {:?} to i32", s))
+        };
+
+        let bounds = text.splitn(2, "..").collect::<Vec<_>>();
+        let from = parse(bounds[0], 0);
+        if bounds[1].starts_with('=') {
+            from..parse(&bounds[1][1..], i32::MAX - 1) + 1
+        } else {
+            from..parse(bounds[1], i32::MAX)
+        }
+    }
+
+    /// Parses "14:00" or "2018-08-25 14:00", seconds default to zero
+    fn parse_moment(text: &str) -> DateTime<Local> {
+        let parts = text.split_whitespace().collect::<Vec<_>>();
+        let midnight = Some(Local::now().date().and_hms(0, 0, 0));
+        match parts.len() {
+            1 => Self::parse_time(parts[0], midnight),
+            2 => Self::parse_datetime(parts[0], parts[1], midnight),
+            _ => panic!("Can't convert {:?} to DateTime<Local>", text),
+        }
+    }
+
+    pub fn parse_date(text: &str) -> Date<Local> {
+        Self::try_parse_date(text).unwrap_or_else(|| panic!("Can't convert date {:?} to Date<Local>", text))
+    }
+
+    /// Parses "now", "25", "08-25" or "2018-08-25", the missing year and month are the current ones
+    pub fn try_parse_date(text: &str) -> Option<Date<Local>> {
         let now = Local::now();
 
         if text == "now" {
-            now.date()
+            Some(now.date())
         } else {
             let mut items = text
                 .split('-')
-                .map(|s| s.parse().expect(&format!("Can't convert part of date {:?} to i32", s)))
-                .collect::<Vec<i32>>();
+                .map(|s| s.parse().ok())
+                .collect::<Option<Vec<i32>>>()?;
             if items.len() < 1 || items.len() > 3 {
-                panic!("Can't convert date {:?} to Date<Local>", text);
+                return None;
             }
             items.reverse();
 
@@ -498,7 +2313,7 @@ impl CmdProcessor {
             } else {
                 now.year()
             };
-            Local.ymd(year, month, day)
+            Local.ymd_opt(year, month, day).single()
         }
     }
 
@@ -535,29 +2350,51 @@ impl CmdProcessor {
         }
     }
 
-    fn get_act(matches: &ArgMatches) -> Option<i64> {
+    fn get_act(matches: &ArgMatches) -> Option<DurationArg> {
         matches.args
             .get(Cmd::ACTIVITY.upcase_name)
+            .map(|arg| {
+                let text = arg.vals[0]
+                    .clone()
+                    .into_string()
+                    .expect(&format!("Can't convert duration of activity {:?} to UTF-8 string", arg.vals[0]));
+                DurationArg::parse(&text).unwrap_or_else(|err| panic!("{}", err))
+            })
+    }
+
+    fn get_rest(matches: &ArgMatches) -> Option<DurationArg> {
+        matches.args
+            .get(Cmd::REST.upcase_name)
+            .map(|arg| {
+                let text = arg.vals[0]
+                    .clone()
+                    .into_string()
+                    .expect(&format!("Can't convert duration of rest {:?} to UTF-8 string", arg.vals[0]));
+                DurationArg::parse(&text).unwrap_or_else(|err| panic!("{}", err))
+            })
+    }
+
+    fn get_replace(matches: &ArgMatches) -> Option<String> {
+        matches.args
+            .get(Cmd::REPLACE.name)
             .map(|arg|
                 arg.vals[0]
                     .clone()
                     .into_string()
-                    .expect(&format!("Can't convert duration of activity {:?} to UTF-8 string", arg.vals[0]))
-                    .parse::<i64>()
-                    .expect(&format!("Can't convert duration of activity {:?} to i64 number", arg.vals[0]))
+                    .expect(&format!("Can't convert replacement {:?} to UTF-8 string", arg.vals[0]))
             )
     }
 
-    fn get_rest(matches: &ArgMatches) -> Option<i64> {
+    fn get_cap(matches: &ArgMatches) -> Option<i64> {
         matches.args
-            .get(Cmd::REST.upcase_name)
+            .get(Cmd::CAP.name)
             .map(|arg|
                 arg.vals[0]
                     .clone()
                     .into_string()
-                    .expect(&format!("Can't convert duration of rest {:?} to UTF-8 string", arg.vals[0]))
+                    .expect(&format!("Can't convert cap {:?} to UTF-8 string", arg.vals[0]))
                     .parse::<i64>()
-                    .expect(&format!("Can't convert duration of rest {:?} to i64 number", arg.vals[0]))
+                    .expect(&format!("Can't convert cap {:?} to i64 number", arg.vals[0]))
             )
     }
 
@@ -565,6 +2402,10 @@ impl CmdProcessor {
         matches.occurrences_of(Cmd::ALL.name) > 0
     }
 
+    fn is_month(matches: &ArgMatches) -> bool {
+        matches.occurrences_of(Cmd::MONTH.name) > 0
+    }
+
     fn is_in_hours(matches: &ArgMatches) -> bool {
         matches.occurrences_of(Cmd::HOURS.name) > 0
     }
@@ -573,6 +2414,38 @@ impl CmdProcessor {
         matches.occurrences_of(Cmd::ROOT.name) > 0
     }
 
+    fn is_with_end(matches: &ArgMatches) -> bool {
+        matches.occurrences_of(Cmd::END.name) > 0
+    }
+
+    fn is_wait(matches: &ArgMatches) -> bool {
+        matches.occurrences_of(Cmd::WAIT.name) > 0
+    }
+
+    fn is_flagged(matches: &ArgMatches) -> bool {
+        matches.occurrences_of(Cmd::FLAGGED.name) > 0
+    }
+
+    fn is_with_id(matches: &ArgMatches) -> bool {
+        matches.occurrences_of(Cmd::ID.name) > 0
+    }
+
+    fn is_yes(matches: &ArgMatches) -> bool {
+        matches.occurrences_of(Cmd::YES.name) > 0
+    }
+
+    fn is_append(matches: &ArgMatches) -> bool {
+        matches.occurrences_of(Cmd::APPEND.name) > 0
+    }
+
+    fn is_force(matches: &ArgMatches) -> bool {
+        matches.occurrences_of(Cmd::FORCE.name) > 0
+    }
+
+    fn is_dry_run(matches: &ArgMatches) -> bool {
+        matches.occurrences_of(Cmd::DRY_RUN.name) > 0
+    }
+
     fn print_total<T: Display>(total: T) {
         let places = format!("{}", total).chars().count();
         let dashes = String::from_utf8(vec![b'-'; 7 + places]).expect("Can't produce dash line");
@@ -0,0 +1,91 @@
+use std::io::Write;
+use clap::{App, Shell};
+
+const BIN_NAME: &str = "tt";
+
+/// The note argument as generated for zsh, which completes file names
+const ZSH_NOTE_ARG: &str = "'::NOTE -- The record note:_files'";
+
+/// Writes the completion script generated from the application arguments
+/// extended with completion of notes from the journal by `tt notes`
+pub fn generate<W: Write>(mut app: App, shell: Shell, out: &mut W) {
+    let mut script = Vec::new();
+    app.gen_completions_to(BIN_NAME, shell, &mut script);
+    let script = String::from_utf8(script).expect("Can't convert completion script to UTF-8 string");
+
+    let script = match shell {
+        Shell::Bash => format!("{}\n{}", script, BASH_NOTES),
+        Shell::Fish => format!("{}\n{}", script, FISH_NOTES),
+        Shell::Zsh => {
+            let script = script.replace(ZSH_NOTE_ARG, "'*::NOTE -- The record note:_tt_notes'");
+            let main_call = script.rfind("_tt \"$@\"").unwrap_or(script.len());
+            format!("{}{}\n{}", &script[..main_call], ZSH_NOTES, &script[main_call..])
+        },
+        _ => script,
+    };
+    out.write_all(script.as_bytes()).expect("Can't write completion script");
+}
+
+const BASH_NOTES: &str = r#"_tt_notes() {
+    local cur="${COMP_WORDS[COMP_CWORD]}" first=0 skip=0 i
+    local words=()
+    case "${COMP_WORDS[1]}" in
+        start|stop|restart)
+            first=2
+            ;;
+        set)
+            for (( i = 2; i < COMP_CWORD; i++ )); do
+                if [[ "${COMP_WORDS[i]}" == "note" ]]; then
+                    first=$(( i + 1 ))
+                    break
+                fi
+            done
+            ;;
+    esac
+    if [[ ${first} -eq 0 || "${cur}" == -* ]]; then
+        _tt
+        return
+    fi
+    for (( i = first; i < COMP_CWORD; i++ )); do
+        case "${COMP_WORDS[i]}" in
+            -n|--offset|-m|--match|--at|--id|--replace)
+                skip=1
+                ;;
+            -*)
+                ;;
+            *)
+                if [[ ${skip} -eq 1 ]]; then
+                    skip=0
+                else
+                    words+=("${COMP_WORDS[i]}")
+                fi
+                ;;
+        esac
+    done
+    COMPREPLY=( $(compgen -W "$(tt notes -- "${words[@]}" 2>/dev/null)" -- "${cur}") )
+}
+
+complete -F _tt_notes -o bashdefault -o default tt
+"#;
+
+const ZSH_NOTES: &str = r#"_tt_notes() {
+    local -a notes
+    notes=(${(f)"$(tt notes -- ${words[1,CURRENT-1]:#-*} 2>/dev/null)"})
+    compadd -a notes
+}
+
+"#;
+
+const FISH_NOTES: &str = r#"function __tt_note_words
+    set -l words (commandline -opc)
+    set -l first 3
+    if test "$words[2]" = set
+        set first (math (contains -i -- note $words) + 1)
+    end
+    if test (count $words) -ge $first
+        string match -v -- '-*' $words[$first..-1]
+    end
+end
+
+complete -c tt -n "__fish_seen_subcommand_from start stop restart; or __fish_seen_subcommand_from note" -f -a "(tt notes -- (__tt_note_words) 2>/dev/null)"
+"#;
@@ -0,0 +1,121 @@
+use tt_core::record::Duration;
+
+/// Duration argument: an absolute value or an adjustment of the current value
+#[derive(Debug, PartialEq)]
+pub enum DurationArg {
+    Set(Duration),
+    Add(Duration),
+}
+
+impl DurationArg {
+    /// Parses "90", "90m", "1h30m", "1:30", "1.5h" and relative "+15m", "-10m"
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text.trim();
+        if let Some(text) = text.strip_prefix('+') {
+            parse_duration(text).map(DurationArg::Add)
+        } else if let Some(text) = text.strip_prefix('-') {
+            parse_duration(text).map(|duration| DurationArg::Add(-duration))
+        } else {
+            parse_duration(text).map(DurationArg::Set)
+        }
+    }
+
+    /// Returns the resulting duration, which is never negative
+    pub fn apply(&self, current: Option<Duration>) -> Duration {
+        let duration = match *self {
+            DurationArg::Set(duration) => duration,
+            DurationArg::Add(duration) => current.unwrap_or_else(Duration::zero) + duration,
+        };
+        if duration < Duration::zero() {
+            Duration::zero()
+        } else {
+            duration
+        }
+    }
+}
+
+pub fn parse_duration(text: &str) -> Result<Duration, String> {
+    let error = || format!("Can't convert {:?} to duration", text);
+
+    if text.contains(':') {
+        let items = text
+            .split(':')
+            .map(|s| s.parse::<u32>().map(i64::from).map_err(|_| error()))
+            .collect::<Result<Vec<_>, _>>()?;
+        return match items.len() {
+            2 => Ok(Duration::hours(items[0]) + Duration::minutes(items[1])),
+            3 => Ok(Duration::hours(items[0]) + Duration::minutes(items[1]) + Duration::seconds(items[2])),
+            _ => Err(error()),
+        };
+    }
+
+    let mut seconds = 0.0;
+    let mut number = String::new();
+    let mut chars = text.chars().peekable();
+    while let Some(ch) = chars.next() {
+        if ch.is_ascii_digit() || ch == '.' {
+            number.push(ch);
+            continue;
+        }
+        let value = number.parse::<f64>().map_err(|_| error())?;
+        number.clear();
+
+        let mut unit = ch.to_string();
+        while let Some(&ch) = chars.peek() {
+            if !ch.is_alphabetic() {
+                break;
+            }
+            unit.push(ch);
+            chars.next();
+        }
+        seconds += value * match unit.as_str() {
+            "h" | "hr" | "hrs" | "hour" | "hours" => 3600.0,
+            "m" | "min" | "mins" | "minute" | "minutes" => 60.0,
+            "s" | "sec" | "secs" | "second" | "seconds" => 1.0,
+            _ => return Err(error()),
+        };
+    }
+    if !number.is_empty() {
+        seconds += number.parse::<f64>().map_err(|_| error())? * 60.0;
+    } else if text.is_empty() {
+        return Err(error());
+    }
+    Ok(Duration::seconds(seconds.round() as i64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_absolute_duration() {
+        assert_eq!(Ok(Duration::minutes(90)), parse_duration("90"));
+        assert_eq!(Ok(Duration::minutes(90)), parse_duration("90m"));
+        assert_eq!(Ok(Duration::minutes(90)), parse_duration("1h30m"));
+        assert_eq!(Ok(Duration::minutes(90)), parse_duration("1:30"));
+        assert_eq!(Ok(Duration::minutes(90)), parse_duration("1.5h"));
+        assert_eq!(Ok(Duration::minutes(120)), parse_duration("2h"));
+        assert!(parse_duration("").is_err());
+        assert!(parse_duration("1x").is_err());
+        assert!(parse_duration("h").is_err());
+        assert!(parse_duration("1:-30").is_err());
+        assert!(parse_duration("-1:30").is_err());
+    }
+
+    #[test]
+    fn parse_relative_duration() {
+        assert_eq!(Ok(DurationArg::Add(Duration::minutes(15))), DurationArg::parse("+15m"));
+        assert_eq!(Ok(DurationArg::Add(Duration::minutes(-10))), DurationArg::parse("-10m"));
+        assert_eq!(Ok(DurationArg::Set(Duration::minutes(45))), DurationArg::parse("45"));
+    }
+
+    #[test]
+    fn apply_duration() {
+        let current = Some(Duration::minutes(30));
+        assert_eq!(Duration::minutes(45), DurationArg::Add(Duration::minutes(15)).apply(current));
+        assert_eq!(Duration::minutes(20), DurationArg::Add(Duration::minutes(-10)).apply(current));
+        assert_eq!(Duration::zero(), DurationArg::Add(Duration::minutes(-40)).apply(current));
+        assert_eq!(Duration::minutes(15), DurationArg::Add(Duration::minutes(15)).apply(None));
+        assert_eq!(Duration::minutes(5), DurationArg::Set(Duration::minutes(5)).apply(current));
+    }
+}
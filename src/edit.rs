@@ -0,0 +1,60 @@
+use std::env;
+use std::path::Path;
+use std::process::Command;
+use tt_core::record::Record;
+use tt_core::journal::file::{FileJournal, Item};
+use crate::temp::TempFile;
+
+/// Opens the journal lines in the user editor and parses the edited records back.
+/// The temporary file is removed whether the edit is accepted or not
+pub fn edit_records(lines: &[String]) -> Result<Vec<Record>, String> {
+    let temp = TempFile::create("tt-edit", format!("{}\n", lines.join("\n")).as_bytes())
+        .map_err(|err| format!("Can't write records to a temporary file: {}", err))?;
+
+    let editor = env::var("VISUAL")
+        .or_else(|_| env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$1\"", editor))
+        .arg(&editor)
+        .arg(&temp.path)
+        .status()
+        .map_err(|err| format!("Can't run editor {:?}: {}", editor, err))?;
+    if !status.success() {
+        return Err(format!("Editor {:?} exited with {}", editor, status));
+    }
+
+    parse_records(&temp.path)
+}
+
+fn parse_records(path: &Path) -> Result<Vec<Record>, String> {
+    let journal = FileJournal::new(path);
+    let mut iter = journal.try_iter()
+        .map_err(|_| format!("Can't read edited records from {:?}", path))?;
+
+    let mut items = Vec::new();
+    iter.go_to_end();
+    while let Some(item) = iter.backward(1).get() {
+        items.push(item);
+    }
+    items.reverse();
+
+    let mut records = Vec::new();
+    for (number, item) in items.into_iter().enumerate() {
+        match item {
+            Item::Record(record) => {
+                if record.start.is_none() {
+                    return Err(format!("The record at line {} has no start: {}", number + 1, record.to_string()));
+                }
+                records.push(record);
+            },
+            Item::SomeLine(line) => {
+                if !line.trim().is_empty() {
+                    return Err(format!("Can't parse line {} as a record: {:?}", number + 1, line));
+                }
+            },
+        }
+    }
+    Ok(records)
+}
@@ -0,0 +1,82 @@
+use tt_core::record::Record;
+use crate::cmd::CmdProcessor;
+use crate::ical::{self, Event};
+use crate::selector::record_id;
+use crate::timesheet::{self, Grouping};
+
+/// Format of the exported records
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    /// iCalendar file with an event for each record
+    Ics,
+    /// Timesheet of comma separated values
+    Csv,
+    /// Timesheet of the Office Open XML workbook
+    Xlsx,
+    /// Timesheet of the OpenDocument spreadsheet
+    Ods,
+}
+
+impl Format {
+    pub const NAMES: [&'static str; 4] = ["ics", "csv", "xlsx", "ods"];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "ics" => Some(Format::Ics),
+            "csv" => Some(Format::Csv),
+            "xlsx" => Some(Format::Xlsx),
+            "ods" => Some(Format::Ods),
+            _ => None,
+        }
+    }
+}
+
+/// Writes the stopped records in the format, the running ones are skipped. The grouping applies to timesheets only
+pub fn write_records(format: Format, grouping: Grouping, records: &[Record]) -> Vec<u8> {
+    match format {
+        Format::Ics => ical::write_events(&events(records)).into_bytes(),
+        Format::Csv => timesheet::to_csv(&timesheet::rows(records, grouping)).into_bytes(),
+        Format::Xlsx => timesheet::to_xlsx(&timesheet::rows(records, grouping)),
+        Format::Ods => timesheet::to_ods(&timesheet::rows(records, grouping)),
+    }
+}
+
+fn events(records: &[Record]) -> Vec<Event> {
+    records
+        .iter()
+        .filter_map(|record| {
+            let start = record.start?;
+            let end = CmdProcessor::end_of(record)?;
+            Some(Event {
+                uid: format!("{}@tt-cli", record_id(record)?),
+                start,
+                end: Some(end),
+                summary: record.note.clone(),
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tt_core::record::{Duration, Local, TimeZone};
+
+    #[test]
+    fn export_record_events() {
+        let mut stopped = Record::now();
+        stopped.start = Some(Local.ymd(2018, 12, 3).and_hms(9, 0, 0));
+        stopped.activity = Some(Duration::minutes(60));
+        stopped.rest = Some(Duration::minutes(5));
+        stopped.note = "Day work task1".to_string();
+        let mut running = Record::now();
+        running.start = Some(Local.ymd(2018, 12, 3).and_hms(11, 0, 0));
+        running.activity = None;
+
+        let events = events(&[stopped, running]);
+        assert_eq!(1, events.len());
+        assert_eq!("20181203090000@tt-cli", events[0].uid);
+        assert_eq!(Some(Local.ymd(2018, 12, 3).and_hms(10, 5, 0)), events[0].end);
+        assert_eq!("Day work task1", events[0].summary);
+    }
+}
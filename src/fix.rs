@@ -0,0 +1,95 @@
+use tt_core::record::{Record, DateTime, Local, Duration};
+use tt_core::journal::file::Item;
+
+pub struct JournalFixer {
+    pub cap: Option<Duration>,
+}
+
+impl JournalFixer {
+    pub fn new(cap: Option<Duration>) -> Self {
+        JournalFixer {
+            cap,
+        }
+    }
+
+    /// Reorders records chronologically, closes dangling open records and trims overlaps.
+    /// Lines that are not records, as well as records without start, stay in place.
+    /// Without the cap, a dangling record is left open when the next record starts on a later day.
+    pub fn fix(&self, items: Vec<Item>) -> Vec<Item> {
+        let mut slots = Vec::new();
+        let mut records = Vec::new();
+        let mut fixed = Vec::with_capacity(items.len());
+
+        for (index, item) in items.into_iter().enumerate() {
+            match item {
+                Item::Record(record) => {
+                    if record.start.is_some() {
+                        slots.push(index);
+                        records.push(record);
+                        fixed.push(None);
+                    } else {
+                        fixed.push(Some(Item::Record(record)));
+                    }
+                },
+                item => fixed.push(Some(item)),
+            }
+        }
+
+        records.sort_by_key(|record| record.start);
+        let next_starts = records.iter()
+            .skip(1)
+            .map(|record| record.start.expect("Sorted record should have start"))
+            .collect::<Vec<_>>();
+        for (record, next_start) in records.iter_mut().zip(next_starts) {
+            self.close_dangling(record, next_start);
+            Self::trim_overlap(record, next_start);
+        }
+
+        for (slot, record) in slots.into_iter().zip(records) {
+            fixed[slot] = Some(Item::Record(record));
+        }
+        fixed.into_iter()
+            .map(|item| item.expect("Fixed journal slot should be filled"))
+            .collect()
+    }
+
+    fn close_dangling(&self, record: &mut Record, next_start: DateTime<Local>) {
+        let over_midnight = record.start.map(|start| start.date() < next_start.date()).unwrap_or(false);
+        if record.activity.is_none() && (self.cap.is_some() || !over_midnight) {
+            let mut act = Self::gap(record, next_start);
+            if let Some(cap) = self.cap {
+                if act > cap {
+                    act = cap;
+                }
+            }
+            record.activity = Some(act);
+        }
+    }
+
+    fn trim_overlap(record: &mut Record, next_start: DateTime<Local>) {
+        let act = record.activity.unwrap_or_else(Duration::zero);
+        let gap = Self::gap(record, next_start);
+        if act > gap {
+            record.activity = Some(gap);
+            if let Some(rest) = record.rest {
+                let start = record.start.expect("Sorted record should have start");
+                let max_rest = next_start.signed_duration_since(start);
+                if rest > max_rest {
+                    record.rest = Some(max_rest);
+                }
+            }
+        }
+    }
+
+    /// Duration of activity that fits between the record start (plus rest) and the next record start.
+    fn gap(record: &Record, next_start: DateTime<Local>) -> Duration {
+        let start = record.start.expect("Sorted record should have start");
+        let rest = record.rest.unwrap_or_else(Duration::zero);
+        let gap = next_start.signed_duration_since(start) - rest;
+        if gap < Duration::zero() {
+            Duration::zero()
+        } else {
+            gap
+        }
+    }
+}
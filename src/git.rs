@@ -0,0 +1,164 @@
+use std::fs;
+use std::path::PathBuf;
+use std::process::Command;
+use regex::Regex;
+use tt_core::record::{DateTime, Local, TimeZone};
+
+/// Commit of the git log
+#[derive(Debug, PartialEq)]
+pub struct Commit {
+    pub hash: String,
+    pub time: DateTime<Local>,
+    pub summary: String,
+}
+
+/// Hash, author time and summary of the commits separated by tabs
+const LOG_FORMAT: &str = "--format=%h%x09%at%x09%s";
+
+/// Issue tracker key of the branch name, for example "ABC-123"
+const TICKET_PATTERN: &str = r"[A-Z][A-Z0-9]*-[0-9]+";
+
+/// Marks the hooks installed by `install_hook`, which may be replaced
+const HOOK_MARK: &str = "# Installed by tt hook install";
+
+/// Switches tracking to the checked out branch, skipping checkouts of files (flag 0) and detached heads
+const POST_CHECKOUT_HOOK: &str = "#!/bin/sh
+# Installed by tt hook install
+[ \"$3\" = \"1\" ] || exit 0
+branch=$(git symbolic-ref --short -q HEAD) || exit 0
+tt hook checkout \"$branch\"
+";
+
+/// Reads the commits of the repository authored within the time range, ordered by time
+pub fn log(repo: &str, from: DateTime<Local>, to: DateTime<Local>) -> Result<Vec<Commit>, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .arg("log")
+        .arg(LOG_FORMAT)
+        .arg(format!("--since={}", from.to_rfc3339()))
+        .output()
+        .map_err(|err| format!("Can't run git: {}", err))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    let mut commits = parse_log(&String::from_utf8_lossy(&output.stdout))?
+        .into_iter()
+        .filter(|commit| commit.time >= from && commit.time < to)
+        .collect::<Vec<_>>();
+    commits.sort_by_key(|commit| commit.time);
+    Ok(commits)
+}
+
+/// Makes the record note of the branch by the template.
+///
+/// The template placeholders are `{branch}` for the whole name, `{type}` for the part before the last slash,
+/// `{ticket}` for the issue key and `{title}` for the rest of the last part with dashes and underscores as spaces.
+/// For example, "feature/ABC-123-login" gives "feature", "ABC-123" and "login".
+pub fn branch_note(template: &str, branch: &str) -> String {
+    let (kind, name) = match branch.rfind('/') {
+        Some(slash) => (&branch[..slash], &branch[slash + 1..]),
+        None => ("", branch),
+    };
+    let ticket_regex = Regex::new(TICKET_PATTERN).expect("Can't compile ticket regex");
+    let ticket = ticket_regex.find(name).map(|found| found.as_str()).unwrap_or_default();
+    let title = name.replacen(ticket, "", 1)
+        .split(&['-', '_'][..])
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+
+    template
+        .replace("{branch}", branch)
+        .replace("{type}", kind)
+        .replace("{ticket}", ticket)
+        .replace("{title}", &title)
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Writes the post-checkout hook to the repository, returns the hook path.
+/// A hook not installed by this function is kept untouched.
+pub fn install_hook(repo: &str) -> Result<PathBuf, String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .arg("rev-parse")
+        .arg("--git-path")
+        .arg("hooks")
+        .output()
+        .map_err(|err| format!("Can't run git: {}", err))?;
+    if !output.status.success() {
+        return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+    }
+
+    let hooks = PathBuf::from(repo).join(String::from_utf8_lossy(&output.stdout).trim());
+    let path = hooks.join("post-checkout");
+    if let Ok(content) = fs::read_to_string(&path) {
+        if !content.contains(HOOK_MARK) {
+            return Err(format!("The hook {:?} already exists, remove it to install a new one", path));
+        }
+    }
+    fs::create_dir_all(&hooks).map_err(|err| format!("Can't create {:?}: {}", hooks, err))?;
+    fs::write(&path, POST_CHECKOUT_HOOK).map_err(|err| format!("Can't write {:?}: {}", path, err))?;
+    make_executable(&path)?;
+    Ok(path)
+}
+
+#[cfg(unix)]
+fn make_executable(path: &PathBuf) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+        .map_err(|err| format!("Can't make {:?} executable: {}", path, err))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &PathBuf) -> Result<(), String> {
+    Ok(())
+}
+
+fn parse_log(output: &str) -> Result<Vec<Commit>, String> {
+    output.lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let fields = line.splitn(3, '\t').collect::<Vec<_>>();
+            match fields.as_slice() {
+                [hash, time, summary] => {
+                    let time = time.parse::<i64>().map_err(|_| format!("Can't parse git log line {:?}", line))?;
+                    Ok(Commit { hash: hash.to_string(), time: Local.timestamp(time, 0), summary: summary.to_string() })
+                },
+                _ => Err(format!("Can't parse git log line {:?}", line)),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_git_log() {
+        let time = Local.ymd(2018, 12, 3).and_hms(9, 30, 0);
+        let output = format!("1a2b3c4\t{}\tFix login form\n\n5d6e7f8\t{}\tAdd tabs\tto menu\n", time.timestamp(), time.timestamp() + 60);
+
+        let commits = parse_log(&output).unwrap();
+        assert_eq!(2, commits.len());
+        assert_eq!(Commit { hash: "1a2b3c4".to_string(), time, summary: "Fix login form".to_string() }, commits[0]);
+        assert_eq!("Add tabs\tto menu", commits[1].summary);
+        assert!(parse_log("1a2b3c4 Fix").is_err());
+    }
+
+    #[test]
+    fn note_of_branch() {
+        let template = "Project {ticket} {title}";
+        assert_eq!("Project ABC-123 login", branch_note(template, "feature/ABC-123-login"));
+        assert_eq!("Project ABC-123 login form", branch_note(template, "ABC-123_login_form"));
+        assert_eq!("Project fix menu", branch_note(template, "bugfix/fix-menu"));
+        assert_eq!("feature ABC-1", branch_note("{type} {ticket}", "feature/ABC-1-login"));
+        assert_eq!("Day work master", branch_note("Day work {branch}", "master"));
+    }
+}
@@ -0,0 +1,470 @@
+use chrono::{Datelike, LocalResult, NaiveDate, NaiveDateTime, Utc, Weekday};
+use tt_core::record::{DateTime, Duration, Local, TimeZone};
+
+const PRODID: &str = "PRODID:-//TimeTracker//tt-cli//EN";
+const MAX_LINE_LENGTH: usize = 75;
+
+/// Event of an iCalendar file
+#[derive(Debug, PartialEq)]
+pub struct Event {
+    pub uid: String,
+    pub start: DateTime<Local>,
+    pub end: Option<DateTime<Local>>,
+    pub summary: String,
+}
+
+/// Parses VEVENT components of the iCalendar content, all-day events are skipped.
+///
+/// Times in UTC are converted to the local time, floating times and times with TZID are taken as local.
+/// Recurring events are expanded to the occurrences started up to `until`, see `Rule` for the supported rules.
+pub fn parse_events(content: &str, until: DateTime<Local>) -> Result<Vec<Event>, String> {
+    let mut events = Vec::new();
+    let mut occurrences = Vec::new();
+    let mut overridden = Vec::new();
+    let mut components: Vec<String> = Vec::new();
+    let mut start = None;
+    let mut end = None;
+    let mut duration = None;
+    let mut summary = String::new();
+    let mut uid = String::new();
+    let mut all_day = false;
+    let mut rule: Option<String> = None;
+    let mut excluded = Vec::new();
+    let mut recurrence_id = None;
+
+    for line in unfold(content) {
+        let (name, params, value) = split_property(&line)
+            .ok_or_else(|| format!("Can't parse iCalendar line {:?}", line))?;
+        // The properties of the components nested in the event, like VALARM, don't belong to the event
+        let in_event = components.last().map(|component| component == "VEVENT").unwrap_or(false);
+        match name.as_str() {
+            "BEGIN" => {
+                components.push(value.to_uppercase());
+                if components.last().map(|component| component == "VEVENT").unwrap_or(false) {
+                    start = None;
+                    end = None;
+                    duration = None;
+                    summary.clear();
+                    uid.clear();
+                    all_day = false;
+                    rule = None;
+                    excluded.clear();
+                    recurrence_id = None;
+                }
+            },
+            "END" => {
+                if components.pop().map(|component| component != "VEVENT").unwrap_or(true) || all_day {
+                    continue;
+                }
+                let start = start.take().ok_or_else(|| format!("The event {:?} has no DTSTART", summary))?;
+                let length = end.take().map(|end| end - start).or_else(|| duration.take());
+                let event = |start| Event { uid: uid.clone(), start, end: length.map(|length| start + length), summary: summary.clone() };
+                if let Some(id) = recurrence_id.take() {
+                    overridden.push((uid.clone(), id));
+                    events.push(event(start));
+                } else if let Some(rule) = rule.take() {
+                    let starts = match Rule::parse(&rule) {
+                        Some(rule) => rule.starts(start, until),
+                        None => {
+                            eprintln!("The recurring event {:?} is taken only once, its rule {:?} is not supported", summary, rule);
+                            vec![start]
+                        },
+                    };
+                    occurrences.extend(starts.into_iter().filter(|start| !excluded.contains(start)).map(event));
+                } else {
+                    events.push(event(start));
+                }
+            },
+            "DTSTART" if in_event => {
+                all_day = params.iter().any(|param| param == "VALUE=DATE") || !value.contains('T');
+                start = Some(parse_datetime(&value)?);
+            },
+            "DTEND" if in_event => end = Some(parse_datetime(&value)?),
+            "DURATION" if in_event => duration = Some(parse_duration(&value)?),
+            "SUMMARY" if in_event => summary = unescape(&value),
+            "UID" if in_event => uid = value,
+            "RRULE" if in_event => rule = Some(value),
+            "EXDATE" if in_event => {
+                for value in value.split(',') {
+                    excluded.push(parse_datetime(value)?);
+                }
+            },
+            "RECURRENCE-ID" if in_event => recurrence_id = Some(parse_datetime(&value)?),
+            _ => (),
+        }
+    }
+    // The occurrences changed by separate events are replaced by them
+    events.extend(occurrences.into_iter().filter(|event| !overridden.contains(&(event.uid.clone(), event.start))));
+    Ok(events)
+}
+
+/// Recurrence rule of an event, only the daily and weekly rules with INTERVAL, COUNT, UNTIL and BYDAY of weekdays
+/// without numbers are supported
+#[derive(Debug, PartialEq)]
+struct Rule {
+    weekly: bool,
+    interval: i64,
+    count: Option<usize>,
+    until: Option<DateTime<Local>>,
+    weekdays: Vec<Weekday>,
+}
+
+impl Rule {
+    /// Parses the RRULE value, returns None for the rules not supported
+    fn parse(text: &str) -> Option<Self> {
+        let mut rule = Rule { weekly: false, interval: 1, count: None, until: None, weekdays: Vec::new() };
+        let mut frequency = None;
+        for part in text.split(';') {
+            let mut pair = part.splitn(2, '=');
+            let (name, value) = (pair.next()?.to_uppercase(), pair.next()?);
+            match name.as_str() {
+                "FREQ" => frequency = Some(value.to_uppercase()),
+                "INTERVAL" => rule.interval = value.parse().ok().filter(|interval| *interval > 0)?,
+                "COUNT" => rule.count = Some(value.parse().ok()?),
+                "UNTIL" => rule.until = Some(parse_datetime(value).ok()?),
+                "BYDAY" => rule.weekdays = value.split(',').map(parse_weekday).collect::<Option<_>>()?,
+                "WKST" => (),
+                _ => return None,
+            }
+        }
+        match frequency?.as_str() {
+            "DAILY" => (),
+            "WEEKLY" => rule.weekly = true,
+            _ => return None,
+        }
+        Some(rule)
+    }
+
+    /// Returns the starts of the occurrences from the first one up to the limit, keeping the local time of the day
+    fn starts(&self, start: DateTime<Local>, limit: DateTime<Local>) -> Vec<DateTime<Local>> {
+        let limit = self.until.map(|until| until.min(limit)).unwrap_or(limit);
+        let days_from_monday = i64::from(start.weekday().num_days_from_monday());
+        let mut starts = vec![start];
+        let mut day = 0;
+        while self.count.map(|count| starts.len() < count).unwrap_or(true) {
+            day += 1;
+            let next = match Local.from_local_datetime(&(start.naive_local() + Duration::days(day))) {
+                LocalResult::Single(next) | LocalResult::Ambiguous(next, _) => next,
+                LocalResult::None => continue,
+            };
+            if next > limit {
+                break;
+            }
+            let period = if self.weekly { (day + days_from_monday) / 7 } else { day };
+            let weekday = if self.weekdays.is_empty() {
+                !self.weekly || next.weekday() == start.weekday()
+            } else {
+                self.weekdays.contains(&next.weekday())
+            };
+            if period % self.interval == 0 && weekday {
+                starts.push(next);
+            }
+        }
+        starts
+    }
+}
+
+fn parse_weekday(text: &str) -> Option<Weekday> {
+    match text.to_uppercase().as_str() {
+        "MO" => Some(Weekday::Mon),
+        "TU" => Some(Weekday::Tue),
+        "WE" => Some(Weekday::Wed),
+        "TH" => Some(Weekday::Thu),
+        "FR" => Some(Weekday::Fri),
+        "SA" => Some(Weekday::Sat),
+        "SU" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// Writes the events as the iCalendar content with times in UTC
+pub fn write_events(events: &[Event]) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        PRODID.to_string(),
+    ];
+    let stamp = format_datetime(Local::now());
+    for event in events {
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", event.uid));
+        lines.push(format!("DTSTAMP:{}", stamp));
+        lines.push(format!("DTSTART:{}", format_datetime(event.start)));
+        if let Some(end) = event.end {
+            lines.push(format!("DTEND:{}", format_datetime(end)));
+        }
+        lines.push(format!("SUMMARY:{}", escape(&event.summary)));
+        lines.push("END:VEVENT".to_string());
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold(line)).collect()
+}
+
+/// Splits the line into parts of at most 75 bytes, continued with a space, and ends it with CRLF
+fn fold(line: &str) -> String {
+    let mut result = String::new();
+    let mut length = 0;
+    for ch in line.chars() {
+        if length + ch.len_utf8() > MAX_LINE_LENGTH {
+            result.push_str("\r\n ");
+            length = 1;
+        }
+        result.push(ch);
+        length += ch.len_utf8();
+    }
+    result.push_str("\r\n");
+    result
+}
+
+/// Joins the folded lines, which continue with a space or a tab
+fn unfold(content: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in content.lines() {
+        let line = line.trim_end_matches('\r');
+        if line.starts_with(' ') || line.starts_with('\t') {
+            if let Some(last) = lines.last_mut() {
+                last.push_str(&line[1..]);
+                continue;
+            }
+        }
+        if !line.is_empty() {
+            lines.push(line.to_string());
+        }
+    }
+    lines
+}
+
+/// Returns the property name, the uppercased parameters like "VALUE=DATE" and the property value
+fn split_property(line: &str) -> Option<(String, Vec<String>, String)> {
+    let mut quoted = false;
+    for (index, ch) in line.char_indices() {
+        match ch {
+            '"' => quoted = !quoted,
+            ':' if !quoted => {
+                let mut parts = line[..index].split(';');
+                let name = parts.next().unwrap_or_default().to_uppercase();
+                let params = parts.map(|param| param.to_uppercase()).collect();
+                return Some((name, params, line[index + 1..].to_string()));
+            },
+            _ => (),
+        }
+    }
+    None
+}
+
+fn unescape(text: &str) -> String {
+    let mut result = String::new();
+    let mut chars = text.chars();
+    while let Some(ch) = chars.next() {
+        if ch == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => result.push(' '),
+                Some(ch) => result.push(ch),
+                None => (),
+            }
+        } else {
+            result.push(ch);
+        }
+    }
+    result
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(',', "\\,")
+        .replace(';', "\\;")
+        .replace('\n', "\\n")
+}
+
+/// Formats the datetime in UTC, for example "20181203T060000Z"
+fn format_datetime(datetime: DateTime<Local>) -> String {
+    datetime.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// Parses "20181203T090000Z", "20181203T090000" and "20181203" of all-day events and dates of rules
+fn parse_datetime(text: &str) -> Result<DateTime<Local>, String> {
+    let error = || format!("Can't convert {:?} to DateTime<Local>", text);
+
+    if let Some(text) = text.strip_suffix('Z') {
+        let naive = NaiveDateTime::parse_from_str(text, "%Y%m%dT%H%M%S").map_err(|_| error())?;
+        return Ok(Utc.from_utc_datetime(&naive).with_timezone(&Local));
+    }
+    let naive = if text.contains('T') {
+        NaiveDateTime::parse_from_str(text, "%Y%m%dT%H%M%S").map_err(|_| error())?
+    } else {
+        NaiveDate::parse_from_str(text, "%Y%m%d").map_err(|_| error())?.and_hms(0, 0, 0)
+    };
+    match Local.from_local_datetime(&naive) {
+        LocalResult::Single(datetime) | LocalResult::Ambiguous(datetime, _) => Ok(datetime),
+        LocalResult::None => Err(error()),
+    }
+}
+
+/// Parses durations like "PT1H30M", "P1D", "-PT15M"
+fn parse_duration(text: &str) -> Result<Duration, String> {
+    let error = || format!("Can't convert {:?} to duration", text);
+    let (negative, text) = match text.chars().next() {
+        Some('-') => (true, &text[1..]),
+        Some('+') => (false, &text[1..]),
+        _ => (false, text),
+    };
+    if !text.starts_with('P') {
+        return Err(error());
+    }
+
+    let mut duration = Duration::zero();
+    let mut number = String::new();
+    for ch in text[1..].chars() {
+        match ch {
+            '0'..='9' => number.push(ch),
+            'T' => (),
+            'W' | 'D' | 'H' | 'M' | 'S' => {
+                let value = number.parse::<i64>().map_err(|_| error())?;
+                number.clear();
+                duration = duration + match ch {
+                    'W' => Duration::weeks(value),
+                    'D' => Duration::days(value),
+                    'H' => Duration::hours(value),
+                    'M' => Duration::minutes(value),
+                    _ => Duration::seconds(value),
+                };
+            },
+            _ => return Err(error()),
+        }
+    }
+    Ok(if negative { -duration } else { duration })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_calendar_events() {
+        let content = "\
+            BEGIN:VCALENDAR\r\n\
+            VERSION:2.0\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:1@example.com\r\n\
+            DTSTART;TZID=\"Europe/Moscow\":20181203T090000\r\n\
+            DTEND;TZID=\"Europe/Moscow\":20181203T093000\r\n\
+            SUMMARY:Daily standup\\, team\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART:20181203T120000\r\n\
+            DURATION:PT1H15M\r\n\
+            SUMMARY:Planning of the\r\n  next sprint\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+
+        let events = parse_events(content, Local::now()).unwrap();
+        assert_eq!(2, events.len());
+        assert_eq!(Local.ymd(2018, 12, 3).and_hms(9, 0, 0), events[0].start);
+        assert_eq!(Some(Local.ymd(2018, 12, 3).and_hms(9, 30, 0)), events[0].end);
+        assert_eq!("Daily standup, team", events[0].summary);
+        assert_eq!(Some(Local.ymd(2018, 12, 3).and_hms(13, 15, 0)), events[1].end);
+        assert_eq!("Planning of the next sprint", events[1].summary);
+    }
+
+    #[test]
+    fn skip_all_day_and_nested_properties() {
+        let content = "\
+            BEGIN:VCALENDAR\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART;VALUE=DATE:20181203\r\n\
+            DTEND;VALUE=DATE:20181204\r\n\
+            SUMMARY:Holiday\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART:20181203T120000\r\n\
+            DURATION:PT1H\r\n\
+            SUMMARY:Sprint planning\r\n\
+            BEGIN:VALARM\r\n\
+            TRIGGER:-PT15M\r\n\
+            DURATION:PT5M\r\n\
+            SUMMARY:Reminder\r\n\
+            END:VALARM\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+
+        let events = parse_events(content, Local::now()).unwrap();
+        assert_eq!(1, events.len());
+        assert_eq!(Some(Local.ymd(2018, 12, 3).and_hms(13, 0, 0)), events[0].end);
+        assert_eq!("Sprint planning", events[0].summary);
+    }
+
+    #[test]
+    fn expand_recurring_events() {
+        let content = "\
+            BEGIN:VCALENDAR\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:standup\r\n\
+            DTSTART:20181203T100000\r\n\
+            DTEND:20181203T101500\r\n\
+            RRULE:FREQ=WEEKLY;BYDAY=MO,WE,FR\r\n\
+            EXDATE:20181207T100000\r\n\
+            SUMMARY:Daily standup\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            UID:standup\r\n\
+            RECURRENCE-ID:20181205T100000\r\n\
+            DTSTART:20181205T110000\r\n\
+            DTEND:20181205T111500\r\n\
+            SUMMARY:Daily standup\r\n\
+            END:VEVENT\r\n\
+            BEGIN:VEVENT\r\n\
+            DTSTART:20181203T150000\r\n\
+            DURATION:PT1H\r\n\
+            RRULE:FREQ=MONTHLY;BYMONTHDAY=3\r\n\
+            SUMMARY:Review\r\n\
+            END:VEVENT\r\n\
+            END:VCALENDAR\r\n";
+
+        let day = |day, hour| Local.ymd(2018, 12, day).and_hms(hour, 0, 0);
+        let events = parse_events(content, day(12, 0)).unwrap();
+        let starts = events.iter().map(|event| event.start).collect::<Vec<_>>();
+        assert_eq!(vec![day(5, 11), day(3, 10), day(10, 10), day(3, 15)], starts);
+        assert_eq!(Some(Local.ymd(2018, 12, 10).and_hms(10, 15, 0)), events[2].end);
+    }
+
+    #[test]
+    fn parse_recurrence_rule() {
+        let rule = Rule::parse("FREQ=DAILY;INTERVAL=2;COUNT=3").unwrap();
+        let start = Local.ymd(2018, 12, 3).and_hms(9, 0, 0);
+        let limit = Local.ymd(2018, 12, 31).and_hms(0, 0, 0);
+        let starts = vec![start, Local.ymd(2018, 12, 5).and_hms(9, 0, 0), Local.ymd(2018, 12, 7).and_hms(9, 0, 0)];
+        assert_eq!(starts, rule.starts(start, limit));
+
+        let rule = Rule::parse("FREQ=WEEKLY;INTERVAL=2;UNTIL=20181218T000000Z").unwrap();
+        assert_eq!(vec![start, Local.ymd(2018, 12, 17).and_hms(9, 0, 0)], rule.starts(start, limit));
+        assert_eq!(None, Rule::parse("FREQ=MONTHLY"));
+        assert_eq!(None, Rule::parse("FREQ=WEEKLY;BYDAY=1MO"));
+    }
+
+    #[test]
+    fn write_calendar_events() {
+        let events = vec![
+            Event {
+                uid: "20181203090000@tt-cli".to_string(),
+                start: Local.ymd(2018, 12, 3).and_hms(9, 0, 0),
+                end: Some(Local.ymd(2018, 12, 3).and_hms(10, 5, 0)),
+                summary: format!("Day work; task1, {}", "long note ".repeat(8)),
+            },
+        ];
+
+        let content = write_events(&events);
+        assert!(content.lines().all(|line| line.len() <= MAX_LINE_LENGTH + 1));
+        assert_eq!(events, parse_events(&content, Local::now()).unwrap());
+    }
+
+    #[test]
+    fn parse_calendar_datetime() {
+        let datetime = Local.ymd(2018, 12, 3).and_hms(9, 0, 0);
+        assert_eq!(Ok(datetime), parse_datetime(&format_datetime(datetime)));
+        assert_eq!(Ok(Local.ymd(2018, 12, 3).and_hms(0, 0, 0)), parse_datetime("20181203"));
+        assert!(parse_datetime("2018-12-03").is_err());
+        assert_eq!(Ok(Duration::minutes(-15)), parse_duration("-PT15M"));
+        assert_eq!(Ok(Duration::days(1)), parse_duration("P1D"));
+    }
+}
@@ -0,0 +1,318 @@
+use std::collections::HashMap;
+use chrono::{LocalResult, NaiveDateTime, Utc};
+use serde::Deserialize;
+use tt_core::record::{Record, Date, DateTime, Duration, Local, TimeZone};
+use crate::duration::parse_duration;
+use crate::ical;
+
+/// Format of the entries exported by other time trackers
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    /// CSV with the header of "start", "end", "activity", "rest" and "note" columns
+    Csv,
+    /// CSV of the Toggl detailed report
+    TogglCsv,
+    /// JSON of `timew export`
+    Timewarrior,
+    /// Events of an iCalendar file
+    Ical,
+}
+
+impl Format {
+    pub const NAMES: [&'static str; 4] = ["csv", "toggl-csv", "timewarrior", "ical"];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "csv" => Some(Format::Csv),
+            "toggl-csv" => Some(Format::TogglCsv),
+            "timewarrior" => Some(Format::Timewarrior),
+            "ical" => Some(Format::Ical),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct TimewarriorInterval {
+    start: String,
+    end: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    annotation: Option<String>,
+}
+
+/// Converts the exported entries to records ordered by start
+pub fn read_records(format: Format, content: &str) -> Result<Vec<Record>, String> {
+    let mut records = match format {
+        Format::Csv => read_csv(content)?,
+        Format::TogglCsv => read_toggl_csv(content)?,
+        Format::Timewarrior => read_timewarrior(content)?,
+        Format::Ical => ical::parse_events(content, Local::now())?
+            .into_iter()
+            .map(|event| new_record(event.start, event.end.map(|end| end - event.start), None, event.summary))
+            .collect(),
+    };
+    records.sort_by_key(|record| record.start);
+    Ok(records)
+}
+
+/// Proposes records for the events started on the date, ordered by start. Events without end are skipped.
+///
+/// The note is the event summary prefixed by the value of the longest `notes` key found in the summary ignoring case.
+pub fn calendar_records(events: Vec<ical::Event>, date: Date<Local>, notes: &HashMap<String, String>) -> Vec<Record> {
+    let mut records = events
+        .into_iter()
+        .filter(|event| event.start.date() == date)
+        .filter_map(|event| {
+            let end = event.end?;
+            let summary = event.summary.to_lowercase();
+            let note = match notes.iter().filter(|(key, _)| summary.contains(key.as_str())).max_by_key(|(key, _)| key.len()) {
+                Some((_, prefix)) => format!("{} {}", prefix, event.summary),
+                None => event.summary,
+            };
+            Some(new_record(event.start, Some(end - event.start), None, note))
+        })
+        .collect::<Vec<_>>();
+    records.sort_by_key(|record| record.start);
+    records
+}
+
+fn read_csv(content: &str) -> Result<Vec<Record>, String> {
+    let mut records = Vec::new();
+    for row in parse_csv_rows(content)? {
+        let start = parse_local(row.get("start").ok_or("The CSV row has no start column")?)?;
+        let rest = match row.get("rest").filter(|text| !text.is_empty()) {
+            Some(rest) => Some(parse_duration(rest)?),
+            None => None,
+        };
+        let activity = match (row.get("activity").filter(|text| !text.is_empty()), row.get("end").filter(|text| !text.is_empty())) {
+            (Some(activity), _) => Some(parse_duration(activity)?),
+            (None, Some(end)) => {
+                let end = parse_local(end)?;
+                if end <= start {
+                    skip_row(start, end);
+                    continue;
+                }
+                Some(end - start - rest.unwrap_or_else(Duration::zero))
+            },
+            (None, None) => None,
+        };
+        let note = row.get("note").cloned().unwrap_or_default();
+        records.push(new_record(start, activity, rest, note));
+    }
+    Ok(records)
+}
+
+fn read_toggl_csv(content: &str) -> Result<Vec<Record>, String> {
+    let mut records = Vec::new();
+    for row in parse_csv_rows(content)? {
+        let field = |name: &str| row.get(name).map(|text| text.trim()).unwrap_or_default();
+        let start = parse_local(&format!("{} {}", field("start date"), field("start time")))?;
+        let end = match field("end date") {
+            "" => None,
+            date => Some(parse_local(&format!("{} {}", date, field("end time")))?),
+        };
+        if let Some(end) = end.filter(|end| *end <= start) {
+            skip_row(start, end);
+            continue;
+        }
+        let activity = if !field("duration").is_empty() {
+            Some(parse_duration(field("duration"))?)
+        } else {
+            end.map(|end| end - start)
+        };
+        let note = [field("project"), field("task"), field("description")]
+            .iter()
+            .filter(|part| !part.is_empty())
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(" ");
+        records.push(new_record(start, activity, None, note));
+    }
+    Ok(records)
+}
+
+fn read_timewarrior(content: &str) -> Result<Vec<Record>, String> {
+    let intervals: Vec<TimewarriorInterval> = serde_json::from_str(content)
+        .map_err(|err| format!("Can't parse Timewarrior export: {}", err))?;
+
+    let mut records = Vec::new();
+    for interval in intervals {
+        let start = parse_utc(&interval.start)?;
+        let activity = match interval.end {
+            Some(end) => Some(parse_utc(&end)? - start),
+            None => None,
+        };
+        let mut note = interval.tags.join(" ");
+        if let Some(annotation) = interval.annotation {
+            note = format!("{} {}", note, annotation).trim().to_string();
+        }
+        records.push(new_record(start, activity, None, note));
+    }
+    Ok(records)
+}
+
+/// Reports the row skipped as its end is not later than the start
+fn skip_row(start: DateTime<Local>, end: DateTime<Local>) {
+    eprintln!(
+        "Skipped the row started at {}, its end {} is not later than the start",
+        start.format(Record::START_DATETIME_FORMAT), end.format(Record::START_DATETIME_FORMAT)
+    );
+}
+
+/// Creates the record, the line breaks of the note are replaced by spaces as the record takes one journal line
+fn new_record(start: DateTime<Local>, activity: Option<Duration>, rest: Option<Duration>, note: String) -> Record {
+    let mut record = Record::now();
+    record.start = Some(start);
+    record.activity = activity;
+    record.rest = rest;
+    record.note = note
+        .split(&['\r', '\n'][..])
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ");
+    record
+}
+
+/// Parses "2018-12-03 09:00:00", "2018-12-03 09:00" or the same with "T" as the local time
+fn parse_local(text: &str) -> Result<DateTime<Local>, String> {
+    let text = text.trim().replace('T', " ");
+    let naive = NaiveDateTime::parse_from_str(&text, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDateTime::parse_from_str(&text, "%Y-%m-%d %H:%M"))
+        .map_err(|_| format!("Can't convert {:?} to DateTime<Local>", text))?;
+    match Local.from_local_datetime(&naive) {
+        LocalResult::Single(datetime) | LocalResult::Ambiguous(datetime, _) => Ok(datetime),
+        LocalResult::None => Err(format!("Can't convert {:?} to DateTime<Local>", text)),
+    }
+}
+
+/// Parses the UTC time of Timewarrior, for example "20181203T090000Z"
+fn parse_utc(text: &str) -> Result<DateTime<Local>, String> {
+    NaiveDateTime::parse_from_str(text, "%Y%m%dT%H%M%SZ")
+        .map(|naive| Utc.from_utc_datetime(&naive).with_timezone(&Local))
+        .map_err(|_| format!("Can't convert {:?} to DateTime<Local>", text))
+}
+
+/// Parses the CSV rows into maps from the lowercased header names to the values
+fn parse_csv_rows(content: &str) -> Result<Vec<HashMap<String, String>>, String> {
+    let mut rows = parse_csv(content).into_iter();
+    let header = rows.next()
+        .ok_or("The CSV has no header")?
+        .into_iter()
+        .map(|name| name.trim().trim_start_matches('\u{feff}').to_lowercase())
+        .collect::<Vec<_>>();
+
+    Ok(rows
+        .filter(|row| row.iter().any(|value| !value.trim().is_empty()))
+        .map(|row| header.iter().cloned().zip(row).collect())
+        .collect())
+}
+
+/// Splits the CSV content into rows of values, supporting quoted values with commas, quotes and newlines
+fn parse_csv(content: &str) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut value = String::new();
+    let mut quoted = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                value.push('"');
+                chars.next();
+            },
+            '"' => quoted = !quoted,
+            ',' if !quoted => row.push(value.split_off(0)),
+            '\r' if !quoted => (),
+            '\n' if !quoted => {
+                row.push(value.split_off(0));
+                rows.push(row.split_off(0));
+            },
+            _ => value.push(ch),
+        }
+    }
+    if !value.is_empty() || !row.is_empty() {
+        row.push(value);
+        rows.push(row);
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn import_csv() {
+        let content = "\
+            start,end,rest,note\n\
+            2018-12-03 09:00,2018-12-03 10:05,5m,\"Day work, task1\"\n\
+            2018-12-03 08:00:00,,,\"Day work \"\"task2\"\"\"\n";
+
+        let records = read_records(Format::Csv, content).unwrap();
+        assert_eq!(2, records.len());
+        assert_eq!(Some(Local.ymd(2018, 12, 3).and_hms(8, 0, 0)), records[0].start);
+        assert_eq!(None, records[0].activity);
+        assert_eq!("Day work \"task2\"", records[0].note);
+        assert_eq!(Some(Duration::minutes(60)), records[1].activity);
+        assert_eq!(Some(Duration::minutes(5)), records[1].rest);
+        assert_eq!("Day work, task1", records[1].note);
+    }
+
+    #[test]
+    fn propose_calendar_records() {
+        let day = Local.ymd(2018, 12, 3);
+        let event = |hour, end: Option<u32>, summary: &str| ical::Event {
+            uid: String::new(),
+            start: day.and_hms(hour, 0, 0),
+            end: end.map(|end| day.and_hms(end, 0, 0)),
+            summary: summary.to_string(),
+        };
+        let events = vec![
+            event(12, Some(13), "Sprint planning"),
+            event(9, Some(10), "Daily standup"),
+            event(10, None, "Reminder"),
+            ical::Event { start: Local.ymd(2018, 12, 4).and_hms(9, 0, 0), ..event(9, Some(10), "Daily standup") },
+        ];
+        let mut notes = HashMap::new();
+        notes.insert("standup".to_string(), "Meetings".to_string());
+        notes.insert("sprint".to_string(), "Meetings sprint".to_string());
+
+        let records = calendar_records(events, day, &notes);
+        assert_eq!(2, records.len());
+        assert_eq!(Some(day.and_hms(9, 0, 0)), records[0].start);
+        assert_eq!(Some(Duration::minutes(60)), records[0].activity);
+        assert_eq!("Meetings Daily standup", records[0].note);
+        assert_eq!("Meetings sprint Sprint planning", records[1].note);
+    }
+
+    #[test]
+    fn import_toggl_csv() {
+        let content = "\
+            User,Email,Client,Project,Task,Description,Billable,Start date,Start time,End date,End time,Duration,Tags\r\n\
+            Joe,joe@example.com,,Day,work,task1,No,2018-12-03,09:00:00,2018-12-03,10:30:00,01:30:00,\r\n";
+
+        let records = read_records(Format::TogglCsv, content).unwrap();
+        assert_eq!(1, records.len());
+        assert_eq!(Some(Local.ymd(2018, 12, 3).and_hms(9, 0, 0)), records[0].start);
+        assert_eq!(Some(Duration::minutes(90)), records[0].activity);
+        assert_eq!("Day work task1", records[0].note);
+    }
+
+    #[test]
+    fn import_timewarrior() {
+        let start = Local.ymd(2018, 12, 3).and_hms(9, 0, 0);
+        let content = format!(
+            r#"[{{"id":1,"start":"{}","end":"{}","tags":["Day","work"],"annotation":"task1"}}]"#,
+            start.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ"),
+            (start + Duration::minutes(45)).with_timezone(&Utc).format("%Y%m%dT%H%M%SZ")
+        );
+
+        let records = read_records(Format::Timewarrior, &content).unwrap();
+        assert_eq!(1, records.len());
+        assert_eq!(Some(start), records[0].start);
+        assert_eq!(Some(Duration::minutes(45)), records[0].activity);
+        assert_eq!("Day work task1", records[0].note);
+    }
+}
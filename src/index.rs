@@ -0,0 +1,230 @@
+use std::fs;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use chrono::NaiveDate;
+use tt_core::journal::file::FileJournal;
+use tt_core::record::{Date, Local};
+use crate::temp::TempFile;
+
+/// Byte offsets of the journal dates, kept in the sidecar file next to the journal.
+///
+/// Each entry starts a run of records of the same date in the file order, its offset is right after the last record
+/// of the previous run, so the lines between records stay with the following run. The first line of the file holds
+/// the journal size and modification time: the index is rebuilt when they change, whatever tool wrote the journal.
+/// The second line holds the offset after the last record.
+pub struct JournalIndex {
+    path: PathBuf,
+    stamp: String,
+    runs: Vec<(NaiveDate, u64)>,
+    records_end: u64,
+}
+
+/// Journal of the lines from the cut offset to the end, copied to a private temporary file while it is read
+pub struct JournalTail {
+    journal: FileJournal,
+    /// Removes the copy of the tail when dropped
+    _temp: Option<TempFile>,
+}
+
+impl JournalIndex {
+    pub const EXTENSION: &'static str = "idx";
+
+    /// Reads the index of the journal, rebuilding it when it's missing or stale
+    pub fn load<P: AsRef<Path>>(journal_path: P) -> Self {
+        let journal_path = journal_path.as_ref();
+        if let Some(index) = Self::current(journal_path) {
+            return index;
+        }
+
+        let content = fs::read_to_string(journal_path).unwrap_or_default();
+        let (runs, records_end) = Self::build(Vec::new(), 0, content.split('\n'));
+        let index = JournalIndex { path: Self::index_path(journal_path), stamp: Self::stamp(journal_path), runs, records_end };
+        index.save();
+        index
+    }
+
+    /// Reads the index of the journal only when it's up to date, without rebuilding it
+    pub fn current<P: AsRef<Path>>(journal_path: P) -> Option<Self> {
+        let journal_path = journal_path.as_ref();
+        let path = Self::index_path(journal_path);
+        let stamp = Self::stamp(journal_path);
+
+        let content = fs::read_to_string(&path).ok()?;
+        let mut lines = content.lines();
+        if lines.next() != Some(stamp.as_str()) {
+            return None;
+        }
+        let records_end = lines.next().and_then(|line| line.parse::<u64>().ok()).unwrap_or(0);
+        let runs = lines
+            .filter_map(|line| {
+                let mut fields = line.splitn(2, '\t');
+                let date = NaiveDate::parse_from_str(fields.next()?, "%Y-%m-%d").ok()?;
+                Some((date, fields.next()?.parse::<u64>().ok()?))
+            })
+            .collect();
+        Some(JournalIndex { path, stamp, runs, records_end })
+    }
+
+    /// Writes the index of the journal lines just written to the journal
+    pub fn update<P: AsRef<Path>>(journal_path: P, lines: &[String]) {
+        let journal_path = journal_path.as_ref();
+        let (runs, records_end) = Self::build(Vec::new(), 0, lines.iter().map(String::as_str));
+        JournalIndex { path: Self::index_path(journal_path), stamp: Self::stamp(journal_path), runs, records_end }.save();
+    }
+
+    /// Brings the index taken before a write up to date, when the write changed only the last run of records:
+    /// the journal is read from the start of the run
+    pub fn update_last_run<P: AsRef<Path>>(mut self, journal_path: P) {
+        let journal_path = journal_path.as_ref();
+        let offset = self.runs.pop().map(|(_, offset)| offset).unwrap_or(0);
+        let mut tail = String::new();
+        let read = fs::File::open(journal_path)
+            .and_then(|mut file| file.seek(SeekFrom::Start(offset)).and_then(|_| file.read_to_string(&mut tail)));
+        // Left stale, the index is rebuilt by the next query
+        if read.is_ok() {
+            let (runs, records_end) = Self::build(self.runs, offset, tail.split('\n'));
+            JournalIndex { path: self.path, stamp: Self::stamp(journal_path), runs, records_end }.save();
+        }
+    }
+
+    /// Offset of the lines a backward walk reads until it meets a record started before the date
+    pub fn cut(&self, date: Date<Local>) -> u64 {
+        let date = date.naive_local();
+        match self.runs.iter().rposition(|(run_date, _)| *run_date < date) {
+            Some(position) => self.runs.get(position + 1).map(|(_, offset)| *offset).unwrap_or(self.records_end),
+            None => 0,
+        }
+    }
+
+    /// Returns the runs of dates and the offset after the last record, continuing the runs of the lines before
+    /// the offset
+    fn build<'a, I: Iterator<Item = &'a str>>(mut runs: Vec<(NaiveDate, u64)>, offset: u64, lines: I) -> (Vec<(NaiveDate, u64)>, u64) {
+        let (mut offset, mut after_record) = (offset, offset);
+        for line in lines {
+            let next = offset + line.len() as u64 + 1;
+            if let Some(date) = Self::line_date(line) {
+                if runs.last().map(|(last, _)| *last != date).unwrap_or(true) {
+                    runs.push((date, after_record));
+                }
+                after_record = next;
+            }
+            offset = next;
+        }
+        (runs, after_record)
+    }
+
+    /// The start date of the record line "[2018-12-03 09:00:00, 60] note", without parsing the whole record
+    fn line_date(line: &str) -> Option<NaiveDate> {
+        if !line.starts_with('[') {
+            return None;
+        }
+        line.get(1..11).and_then(|date| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+    }
+
+    fn save(&self) {
+        let mut content = format!("{}\n{}\n", self.stamp, self.records_end);
+        for (date, offset) in self.runs.iter() {
+            content.push_str(&format!("{}\t{}\n", date.format("%Y-%m-%d"), offset));
+        }
+        // The index is only a shortcut, the journal is read whole without it
+        fs::write(&self.path, content).ok();
+    }
+
+    fn index_path(journal_path: &Path) -> PathBuf {
+        let mut path = journal_path.as_os_str().to_owned();
+        path.push(".");
+        path.push(Self::EXTENSION);
+        PathBuf::from(path)
+    }
+
+    fn stamp(journal_path: &Path) -> String {
+        fs::metadata(journal_path)
+            .map(|metadata| {
+                let modified = metadata.modified().ok()
+                    .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+                    .map(|modified| format!("{}.{:09}", modified.as_secs(), modified.subsec_nanos()))
+                    .unwrap_or_default();
+                format!("{}\t{}", metadata.len(), modified)
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl JournalTail {
+    /// Opens the journal lines a backward walk reads until it meets a record started before the date,
+    /// the whole journal without the date. The journal is read in place when the cut leaves most of it,
+    /// as copying the tail then costs more than it saves.
+    pub fn open<P: AsRef<Path>>(journal_path: P, date: Option<Date<Local>>) -> Self {
+        let journal_path = journal_path.as_ref();
+        let cut = date.map(|date| JournalIndex::load(journal_path).cut(date)).unwrap_or(0);
+        let length = fs::metadata(journal_path).map(|metadata| metadata.len()).unwrap_or(0);
+        if cut <= length / 2 {
+            return JournalTail { journal: FileJournal::new(journal_path), _temp: None };
+        }
+
+        let mut tail = Vec::new();
+        if let Ok(mut file) = fs::File::open(journal_path) {
+            if cut < length {
+                file.seek(SeekFrom::Start(cut))
+                    .and_then(|_| file.read_to_end(&mut tail))
+                    .expect(&format!("Can't read journal {:?}", journal_path));
+            }
+        }
+        let temp = TempFile::create("tt-tail", &tail).expect("Can't write journal tail to a temporary file");
+        JournalTail { journal: FileJournal::new(&temp.path), _temp: Some(temp) }
+    }
+
+    pub fn journal(&self) -> &FileJournal {
+        &self.journal
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tt_core::record::TimeZone;
+
+    #[test]
+    fn cut_journal_by_date() {
+        let lines = vec![
+            "[2018-12-02 09:00:00, 60] Day work task1",
+            "[2018-12-03 09:00:00, 60] Day work task2",
+            "",
+            "[2018-12-03 11:00:00, 30] Day work task3",
+            "# moved",
+            "[2018-12-01 12:00:00, 30] Lunch",
+            "",
+            "[2018-12-05 09:00:00, 60] Day work task4",
+            "# end",
+        ];
+        let (runs, records_end) = JournalIndex::build(Vec::new(), 0, lines.iter().cloned());
+        let offset = |line: usize| lines[..line].iter().map(|line| line.len() as u64 + 1).sum::<u64>();
+        let index = JournalIndex { path: PathBuf::new(), stamp: String::new(), runs, records_end };
+
+        assert_eq!(0, index.cut(Local.ymd(2018, 12, 1)));
+        assert_eq!(offset(6), index.cut(Local.ymd(2018, 12, 2)));
+        assert_eq!(offset(6), index.cut(Local.ymd(2018, 12, 5)));
+        assert_eq!(offset(8), index.cut(Local.ymd(2018, 12, 6)));
+    }
+
+    #[test]
+    fn build_from_last_run() {
+        let lines = [
+            "[2018-12-02 09:00:00, 60] Day work task1",
+            "# moved",
+            "[2018-12-03 09:00:00, 60] Day work task2",
+            "[2018-12-03 11:00:00, 30] Day work task3",
+            "[2018-12-04 09:00:00, 60] Day work task4",
+            "",
+        ];
+        let offset = |line: usize| lines[..line].iter().map(|line| line.len() as u64 + 1).sum::<u64>();
+        let (mut runs, _) = JournalIndex::build(Vec::new(), 0, lines[..4].iter().cloned());
+        let (_, last_offset) = runs.pop().unwrap();
+        assert_eq!(offset(1), last_offset);
+
+        let built = JournalIndex::build(Vec::new(), 0, lines.iter().cloned());
+        assert_eq!(built, JournalIndex::build(runs.clone(), last_offset, lines[1..].iter().cloned()));
+        assert_eq!((runs.clone(), offset(1)), JournalIndex::build(runs, last_offset, [""].iter().cloned()));
+    }
+}
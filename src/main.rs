@@ -1,31 +1,84 @@
+mod alias;
 mod cmd;
+mod completion;
+mod duration;
+mod edit;
+mod export;
+mod fix;
+mod git;
+mod ical;
+mod import;
+mod index;
+mod meta;
+mod pomodoro;
 mod report;
+mod selector;
+mod server;
 mod settings;
+mod summary;
+mod temp;
+mod timesheet;
+mod tui;
+mod zip;
 
-use clap::{App, Arg, SubCommand};
+use std::io;
+use clap::{App, AppSettings, Arg, Shell, SubCommand};
 use crate::cmd::{Cmd, CmdProcessor};
+use crate::import::Format;
+use crate::server::Server;
 use crate::settings::Settings;
+use crate::tui::Tui;
 
 const VERSION: &'static str = env!("CARGO_PKG_VERSION");
 
-fn main() {
-    let settings = Settings::new().expect("Read settings error");
-    let matches = App::new("TimeTracker CLI")
+fn build_app() -> App<'static, 'static> {
+    App::new("TimeTracker CLI")
         .version(VERSION)
         .about("The command line interface of TimeTracker")
         .subcommand(SubCommand::with_name(Cmd::START.name)
             .about(Cmd::START.desc)
+            .arg(Arg::with_name(Cmd::FOR.name)
+                .long(Cmd::FOR.name)
+                .value_name(Cmd::FOR.upcase_name)
+                .help(Cmd::FOR.desc)
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::WAIT.name)
+                .short(Cmd::WAIT.short)
+                .long(Cmd::WAIT.name)
+                .help(Cmd::WAIT.desc)
+                .requires(Cmd::FOR.name))
             .arg(Arg::with_name(Cmd::NOTE.upcase_name)
                 .help(Cmd::NOTE.desc)
                 .multiple(true)))
         .subcommand(SubCommand::with_name(Cmd::STOP.name)
             .about(Cmd::STOP.desc)
+            .arg(Arg::with_name(Cmd::IDLE.name)
+                .long(Cmd::IDLE.name)
+                .value_name(Cmd::IDLE.upcase_name)
+                .help(Cmd::IDLE.desc)
+                .takes_value(true))
             .arg(Arg::with_name(Cmd::OFFSET.name)
                 .short(Cmd::OFFSET.short)
                 .long(Cmd::OFFSET.name)
                 .value_name(Cmd::OFFSET.upcase_name)
                 .help(Cmd::OFFSET.desc)
                 .takes_value(true))
+            .arg(Arg::with_name(Cmd::MATCH.name)
+                .short(Cmd::MATCH.short)
+                .long(Cmd::MATCH.name)
+                .value_name(Cmd::MATCH.upcase_name)
+                .help(Cmd::MATCH.desc)
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::AT.name)
+                .long(Cmd::AT.name)
+                .value_name(Cmd::AT.upcase_name)
+                .help(Cmd::AT.desc)
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::ID.name)
+                .long(Cmd::ID.name)
+                .value_name(Cmd::ID.upcase_name)
+                .help(Cmd::ID.desc)
+                .takes_value(true))
             .arg(Arg::with_name(Cmd::NOTE.upcase_name)
                 .help(Cmd::NOTE.desc)
                 .multiple(true)))
@@ -37,15 +90,46 @@ fn main() {
                 .value_name(Cmd::OFFSET.upcase_name)
                 .help(Cmd::OFFSET.desc)
                 .takes_value(true))
+            .arg(Arg::with_name(Cmd::MATCH.name)
+                .short(Cmd::MATCH.short)
+                .long(Cmd::MATCH.name)
+                .value_name(Cmd::MATCH.upcase_name)
+                .help(Cmd::MATCH.desc)
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::AT.name)
+                .long(Cmd::AT.name)
+                .value_name(Cmd::AT.upcase_name)
+                .help(Cmd::AT.desc)
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::ID.name)
+                .long(Cmd::ID.name)
+                .value_name(Cmd::ID.upcase_name)
+                .help(Cmd::ID.desc)
+                .takes_value(true))
             .arg(Arg::with_name(Cmd::NOTE.upcase_name)
                 .help(Cmd::NOTE.desc)
                 .multiple(true)))
+        .subcommand(SubCommand::with_name(Cmd::RESUME.name)
+            .about(Cmd::RESUME.desc)
+            .arg(Arg::with_name(Cmd::QUERY.upcase_name)
+                .help(Cmd::QUERY.desc)
+                .multiple(true)))
         .subcommand(SubCommand::with_name(Cmd::LIST.name)
             .about(Cmd::LIST.desc)
             .arg(Arg::with_name(Cmd::ALL.name)
                 .short(Cmd::ALL.short)
                 .long(Cmd::ALL.name)
                 .help(Cmd::ALL.desc))
+            .arg(Arg::with_name(Cmd::END.name)
+                .short(Cmd::END.short)
+                .long(Cmd::END.name)
+                .help("Show the end time of records"))
+            .arg(Arg::with_name(Cmd::ID.name)
+                .long(Cmd::ID.name)
+                .help("Show record identifiers"))
+            .arg(Arg::with_name(Cmd::FLAGGED.name)
+                .long(Cmd::FLAGGED.name)
+                .help(Cmd::FLAGGED.desc))
             .arg(Arg::with_name(Cmd::DATE.upcase_name)
                 .help(Cmd::DATE.desc)))
         .subcommand(SubCommand::with_name(Cmd::DEL.name)
@@ -55,7 +139,36 @@ fn main() {
                 .long(Cmd::OFFSET.name)
                 .value_name(Cmd::OFFSET.upcase_name)
                 .help(Cmd::OFFSET.desc)
-                .takes_value(true)))
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::MATCH.name)
+                .short(Cmd::MATCH.short)
+                .long(Cmd::MATCH.name)
+                .value_name(Cmd::MATCH.upcase_name)
+                .help(Cmd::MATCH.desc)
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::ALL_MATCHES.name)
+                .long(Cmd::ALL_MATCHES.name)
+                .help(Cmd::ALL_MATCHES.desc)
+                .requires(Cmd::MATCH.name))
+            .arg(Arg::with_name(Cmd::AT.name)
+                .long(Cmd::AT.name)
+                .value_name(Cmd::AT.upcase_name)
+                .help(Cmd::AT.desc)
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::ID.name)
+                .long(Cmd::ID.name)
+                .value_name(Cmd::ID.upcase_name)
+                .help(Cmd::ID.desc)
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::DATE.name)
+                .long(Cmd::DATE.name)
+                .value_name(Cmd::DATE.upcase_name)
+                .help("Select records started on the date")
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::YES.name)
+                .short(Cmd::YES.short)
+                .long(Cmd::YES.name)
+                .help(Cmd::YES.desc)))
         .subcommand(SubCommand::with_name(Cmd::REPORT.name)
             .about(Cmd::REPORT.desc)
             .arg(Arg::with_name(Cmd::ALL.name)
@@ -72,16 +185,220 @@ fn main() {
                 .help(Cmd::ROOT.desc))
             .arg(Arg::with_name(Cmd::DATE.upcase_name)
                 .help(Cmd::DATE.desc)))
+        .subcommand(SubCommand::with_name(Cmd::SUMMARY.name)
+            .about(Cmd::SUMMARY.desc)
+            .arg(Arg::with_name(Cmd::WEEK.name)
+                .short(Cmd::WEEK.short)
+                .long(Cmd::WEEK.name)
+                .help(Cmd::WEEK.desc)
+                .conflicts_with(Cmd::MONTH.name))
+            .arg(Arg::with_name(Cmd::MONTH.name)
+                .short(Cmd::MONTH.short)
+                .long(Cmd::MONTH.name)
+                .help(Cmd::MONTH.desc))
+            .arg(Arg::with_name(Cmd::DATE.upcase_name)
+                .help("Date within the period, today by default")))
+        .subcommand(SubCommand::with_name(Cmd::FIX.name)
+            .about(Cmd::FIX.desc)
+            .arg(Arg::with_name(Cmd::DRY_RUN.name)
+                .short(Cmd::DRY_RUN.short)
+                .long(Cmd::DRY_RUN.name)
+                .help(Cmd::DRY_RUN.desc))
+            .arg(Arg::with_name(Cmd::CAP.name)
+                .long(Cmd::CAP.name)
+                .value_name(Cmd::CAP.upcase_name)
+                .help(Cmd::CAP.desc)
+                .takes_value(true)))
+        .subcommand(SubCommand::with_name(Cmd::EDIT.name)
+            .about(Cmd::EDIT.desc)
+            .arg(Arg::with_name(Cmd::OFFSET.name)
+                .short(Cmd::OFFSET.short)
+                .long(Cmd::OFFSET.name)
+                .value_name(Cmd::OFFSET.upcase_name)
+                .help(Cmd::OFFSET.desc)
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::DATE.name)
+                .long(Cmd::DATE.name)
+                .value_name(Cmd::DATE.upcase_name)
+                .help("Select records started on the date")
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::MATCH.name)
+                .short(Cmd::MATCH.short)
+                .long(Cmd::MATCH.name)
+                .value_name(Cmd::MATCH.upcase_name)
+                .help(Cmd::MATCH.desc)
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::ALL_MATCHES.name)
+                .long(Cmd::ALL_MATCHES.name)
+                .help(Cmd::ALL_MATCHES.desc)
+                .requires(Cmd::MATCH.name))
+            .arg(Arg::with_name(Cmd::FORCE.name)
+                .short(Cmd::FORCE.short)
+                .long(Cmd::FORCE.name)
+                .help(Cmd::FORCE.desc)))
+        .subcommand(SubCommand::with_name(Cmd::IMPORT.name)
+            .about(Cmd::IMPORT.desc)
+            .arg(Arg::with_name(Cmd::FROM.name)
+                .long(Cmd::FROM.name)
+                .value_name(Cmd::FROM.upcase_name)
+                .help(Cmd::FROM.desc)
+                .possible_values(&Format::NAMES)
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name(Cmd::FILE.upcase_name)
+                .help(Cmd::FILE.desc)
+                .required(true)))
+        .subcommand(SubCommand::with_name(Cmd::IMPORT_CALENDAR.name)
+            .about(Cmd::IMPORT_CALENDAR.desc)
+            .arg(Arg::with_name(Cmd::DATE.name)
+                .long(Cmd::DATE.name)
+                .value_name(Cmd::DATE.upcase_name)
+                .help("Propose records for the events of the date, today by default")
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::YES.name)
+                .short(Cmd::YES.short)
+                .long(Cmd::YES.name)
+                .help("Add all proposed records without confirmation"))
+            .arg(Arg::with_name(Cmd::FILE.upcase_name)
+                .help(Cmd::FILE.desc)
+                .required(true)))
+        .subcommand(SubCommand::with_name(Cmd::GIT_LOG.name)
+            .about(Cmd::GIT_LOG.desc)
+            .arg(Arg::with_name(Cmd::REPO.name)
+                .long(Cmd::REPO.name)
+                .value_name(Cmd::REPO.upcase_name)
+                .help(Cmd::REPO.desc)
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::DATE.name)
+                .long(Cmd::DATE.name)
+                .value_name(Cmd::DATE.upcase_name)
+                .help("First date of the commits, today by default")
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::TO.name)
+                .long(Cmd::TO.name)
+                .value_name(Cmd::DATE.upcase_name)
+                .help("Last date of the commits, the first date by default")
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::APPEND.name)
+                .long(Cmd::APPEND.name)
+                .help(Cmd::APPEND.desc))
+            .arg(Arg::with_name(Cmd::YES.name)
+                .short(Cmd::YES.short)
+                .long(Cmd::YES.name)
+                .help("Append the commit summaries without confirmation")))
+        .subcommand(SubCommand::with_name(Cmd::HOOK.name)
+            .about(Cmd::HOOK.desc)
+            .setting(AppSettings::SubcommandRequiredElseHelp)
+            .subcommand(SubCommand::with_name(Cmd::INSTALL.name)
+                .about(Cmd::INSTALL.desc)
+                .arg(Arg::with_name(Cmd::REPO.name)
+                    .long(Cmd::REPO.name)
+                    .value_name(Cmd::REPO.upcase_name)
+                    .help(Cmd::REPO.desc)
+                    .takes_value(true)))
+            .subcommand(SubCommand::with_name(Cmd::CHECKOUT.name)
+                .about(Cmd::CHECKOUT.desc)
+                .arg(Arg::with_name(Cmd::BRANCH.upcase_name)
+                    .help(Cmd::BRANCH.desc)
+                    .required(true))))
+        .subcommand(SubCommand::with_name(Cmd::SERVE.name)
+            .about(Cmd::SERVE.desc)
+            .arg(Arg::with_name(Cmd::PORT.name)
+                .short(Cmd::PORT.short)
+                .long(Cmd::PORT.name)
+                .value_name(Cmd::PORT.upcase_name)
+                .help(Cmd::PORT.desc)
+                .default_value("7878")
+                .takes_value(true)))
+        .subcommand(SubCommand::with_name(Cmd::EXPORT.name)
+            .about(Cmd::EXPORT.desc)
+            .arg(Arg::with_name(Cmd::FORMAT.name)
+                .long(Cmd::FORMAT.name)
+                .value_name(Cmd::FORMAT.upcase_name)
+                .help(Cmd::FORMAT.desc)
+                .possible_values(&export::Format::NAMES)
+                .takes_value(true)
+                .required(true))
+            .arg(Arg::with_name(Cmd::BY.name)
+                .long(Cmd::BY.name)
+                .value_name(Cmd::BY.upcase_name)
+                .help(Cmd::BY.desc)
+                .possible_values(&timesheet::Grouping::NAMES)
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::FROM.name)
+                .long(Cmd::FROM.name)
+                .value_name(Cmd::DATE.upcase_name)
+                .help("First start date of the exported records, for example: \"2018-08-25\", \"08-25\", \"25\", \"now\"")
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::TO.name)
+                .long(Cmd::TO.name)
+                .value_name(Cmd::DATE.upcase_name)
+                .help(Cmd::TO.desc)
+                .takes_value(true)))
+        .subcommand(SubCommand::with_name(Cmd::TUI.name)
+            .about(Cmd::TUI.desc))
         .subcommand(SubCommand::with_name(Cmd::SET.name)
             .about(Cmd::SET.desc)
+            .arg(Arg::with_name(Cmd::FORCE.name)
+                .short(Cmd::FORCE.short)
+                .long(Cmd::FORCE.name)
+                .help(Cmd::FORCE.desc))
             .arg(Arg::with_name(Cmd::OFFSET.name)
                 .short(Cmd::OFFSET.short)
                 .long(Cmd::OFFSET.name)
                 .value_name(Cmd::OFFSET.upcase_name)
                 .help(Cmd::OFFSET.desc)
                 .takes_value(true))
+            .arg(Arg::with_name(Cmd::MATCH.name)
+                .short(Cmd::MATCH.short)
+                .long(Cmd::MATCH.name)
+                .value_name(Cmd::MATCH.upcase_name)
+                .help(Cmd::MATCH.desc)
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::ALL_MATCHES.name)
+                .long(Cmd::ALL_MATCHES.name)
+                .help(Cmd::ALL_MATCHES.desc)
+                .requires(Cmd::MATCH.name))
+            .arg(Arg::with_name(Cmd::AT.name)
+                .long(Cmd::AT.name)
+                .value_name(Cmd::AT.upcase_name)
+                .help(Cmd::AT.desc)
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::ID.name)
+                .long(Cmd::ID.name)
+                .value_name(Cmd::ID.upcase_name)
+                .help(Cmd::ID.desc)
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::DATE.name)
+                .long(Cmd::DATE.name)
+                .value_name(Cmd::DATE.upcase_name)
+                .help("Select records started on the date")
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::YES.name)
+                .short(Cmd::YES.short)
+                .long(Cmd::YES.name)
+                .help(Cmd::YES.desc))
             .subcommand(SubCommand::with_name(Cmd::NOTE.name)
                 .about(Cmd::NOTE.desc)
+                .arg(Arg::with_name(Cmd::MATCH.name)
+                    .short(Cmd::MATCH.short)
+                    .long(Cmd::MATCH.name)
+                    .value_name(Cmd::MATCH.upcase_name)
+                    .help(Cmd::MATCH.desc)
+                    .takes_value(true))
+                .arg(Arg::with_name(Cmd::ALL_MATCHES.name)
+                    .long(Cmd::ALL_MATCHES.name)
+                    .help(Cmd::ALL_MATCHES.desc)
+                    .requires(Cmd::MATCH.name))
+                .arg(Arg::with_name(Cmd::REPLACE.name)
+                    .long(Cmd::REPLACE.name)
+                    .value_name(Cmd::REPLACE.upcase_name)
+                    .help(Cmd::REPLACE.desc)
+                    .takes_value(true))
+                .arg(Arg::with_name(Cmd::YES.name)
+                    .short(Cmd::YES.short)
+                    .long(Cmd::YES.name)
+                    .help(Cmd::YES.desc))
                 .arg(Arg::with_name(Cmd::NOTE.upcase_name)
                     .help(Cmd::NOTE.desc)
                     .multiple(true)))
@@ -91,6 +408,7 @@ fn main() {
                     .help(Cmd::DATE.desc)))
             .subcommand(SubCommand::with_name(Cmd::TIME.name)
                 .about(Cmd::TIME.desc)
+                .setting(AppSettings::AllowLeadingHyphen)
                 .arg(Arg::with_name(Cmd::TIME.upcase_name)
                     .help(Cmd::TIME.desc)))
             .subcommand(SubCommand::with_name(Cmd::DATETIME.name)
@@ -98,15 +416,67 @@ fn main() {
                 .arg(Arg::with_name(Cmd::DATETIME.upcase_name)
                     .help(Cmd::DATETIME.desc)
                     .multiple(true)))
+            .subcommand(SubCommand::with_name(Cmd::END.name)
+                .about(Cmd::END.desc)
+                .arg(Arg::with_name(Cmd::END.upcase_name)
+                    .help(Cmd::END.desc)
+                    .min_values(1)
+                    .max_values(2)))
             .subcommand(SubCommand::with_name(Cmd::ACTIVITY.name)
                 .about(Cmd::ACTIVITY.desc)
+                .setting(AppSettings::AllowLeadingHyphen)
                 .arg(Arg::with_name(Cmd::ACTIVITY.upcase_name)
                     .help(Cmd::ACTIVITY.desc)))
             .subcommand(SubCommand::with_name(Cmd::REST.name)
                 .about(Cmd::REST.desc)
+                .setting(AppSettings::AllowLeadingHyphen)
                 .arg(Arg::with_name(Cmd::REST.upcase_name)
                     .help(Cmd::REST.desc))))
-        .get_matches();
+        .subcommand(SubCommand::with_name(Cmd::STATUS.name)
+            .about(Cmd::STATUS.desc)
+            .arg(Arg::with_name(Cmd::FORMAT.name)
+                .long(Cmd::FORMAT.name)
+                .value_name(Cmd::FORMAT.upcase_name)
+                .help("Print one line by the template for status bars, with {note}, {project}, {elapsed} and {today_total}, \
+                    for example: \"{project} {elapsed} ({today_total})\"")
+                .takes_value(true)))
+        .subcommand(SubCommand::with_name(Cmd::POMODORO.name)
+            .about(Cmd::POMODORO.desc)
+            .arg(Arg::with_name(Cmd::WORK.name)
+                .long(Cmd::WORK.name)
+                .value_name(Cmd::WORK.upcase_name)
+                .help(Cmd::WORK.desc)
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::BREAK.name)
+                .long(Cmd::BREAK.name)
+                .value_name(Cmd::BREAK.upcase_name)
+                .help(Cmd::BREAK.desc)
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::CYCLES.name)
+                .long(Cmd::CYCLES.name)
+                .value_name(Cmd::CYCLES.upcase_name)
+                .help(Cmd::CYCLES.desc)
+                .takes_value(true))
+            .arg(Arg::with_name(Cmd::NOTE.upcase_name)
+                .help(Cmd::NOTE.desc)
+                .multiple(true)))
+        .subcommand(SubCommand::with_name(Cmd::COMPLETIONS.name)
+            .about(Cmd::COMPLETIONS.desc)
+            .arg(Arg::with_name(Cmd::SHELL.upcase_name)
+                .help(Cmd::SHELL.desc)
+                .possible_values(&Shell::variants())
+                .required(true)))
+        .subcommand(SubCommand::with_name(Cmd::NOTES.name)
+            .about(Cmd::NOTES.desc)
+            .setting(AppSettings::Hidden)
+            .arg(Arg::with_name(Cmd::NOTE.upcase_name)
+                .help("Words of the note typed so far")
+                .multiple(true)))
+}
+
+fn main() {
+    let settings = Settings::new().expect("Read settings error");
+    let matches = build_app().get_matches();
 
     let mut processor = CmdProcessor::new(&settings);
     if let Some(matches) = matches.subcommand_matches(Cmd::START.name) {
@@ -115,6 +485,8 @@ fn main() {
         processor.stop(matches);
     } else if let Some(matches) = matches.subcommand_matches(Cmd::RESTART.name) {
         processor.restart(matches);
+    } else if let Some(matches) = matches.subcommand_matches(Cmd::RESUME.name) {
+        processor.resume(matches);
     } else if let Some(matches) = matches.subcommand_matches(Cmd::LIST.name) {
         processor.list(matches);
     } else if let Some(matches) = matches.subcommand_matches(Cmd::SET.name) {
@@ -123,5 +495,53 @@ fn main() {
         processor.del(matches);
     } else if let Some(matches) = matches.subcommand_matches(Cmd::REPORT.name) {
         processor.report(matches);
+    } else if let Some(matches) = matches.subcommand_matches(Cmd::SUMMARY.name) {
+        processor.summary(matches);
+    } else if let Some(matches) = matches.subcommand_matches(Cmd::FIX.name) {
+        processor.fix(matches);
+    } else if let Some(matches) = matches.subcommand_matches(Cmd::EDIT.name) {
+        processor.edit(matches);
+    } else if let Some(matches) = matches.subcommand_matches(Cmd::IMPORT.name) {
+        processor.import(matches);
+    } else if let Some(matches) = matches.subcommand_matches(Cmd::IMPORT_CALENDAR.name) {
+        processor.import_calendar(matches);
+    } else if let Some(matches) = matches.subcommand_matches(Cmd::GIT_LOG.name) {
+        processor.git_log(matches);
+    } else if let Some(matches) = matches.subcommand_matches(Cmd::HOOK.name) {
+        if let Some(matches) = matches.subcommand_matches(Cmd::INSTALL.name) {
+            processor.install_hook(matches);
+        } else if let Some(matches) = matches.subcommand_matches(Cmd::CHECKOUT.name) {
+            processor.checkout(matches);
+        }
+    } else if let Some(matches) = matches.subcommand_matches(Cmd::SERVE.name) {
+        let port = matches.args.get(Cmd::PORT.name)
+            .and_then(|arg| arg.vals[0].to_str())
+            .and_then(|port| port.parse::<u16>().ok())
+            .expect("Port should be a number from 0 to 65535");
+        let mut settings = settings.clone();
+        settings.print = false;
+        Server::new(&mut CmdProcessor::new(&settings), port)
+            .run()
+            .expect("Can't run server");
+    } else if let Some(matches) = matches.subcommand_matches(Cmd::EXPORT.name) {
+        processor.export(matches);
+    } else if matches.subcommand_matches(Cmd::TUI.name).is_some() {
+        let mut settings = settings.clone();
+        settings.print = false;
+        Tui::new(&mut CmdProcessor::new(&settings))
+            .run()
+            .expect("Can't run terminal interface");
+    } else if let Some(matches) = matches.subcommand_matches(Cmd::STATUS.name) {
+        processor.status(matches);
+    } else if let Some(matches) = matches.subcommand_matches(Cmd::POMODORO.name) {
+        processor.pomodoro(matches);
+    } else if let Some(matches) = matches.subcommand_matches(Cmd::COMPLETIONS.name) {
+        let shell = matches.args.get(Cmd::SHELL.upcase_name)
+            .and_then(|arg| arg.vals[0].to_str())
+            .and_then(|shell| shell.parse::<Shell>().ok())
+            .expect("Unknown shell");
+        completion::generate(build_app(), shell, &mut io::stdout());
+    } else if let Some(matches) = matches.subcommand_matches(Cmd::NOTES.name) {
+        processor.notes(matches);
     }
 }
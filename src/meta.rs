@@ -0,0 +1,138 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Record attributes that don't fit the journal line format, kept in the sidecar file next to the journal.
+///
+/// Each line of the file holds the record identifier (see `selector::record_id`), the attribute key and value
+/// separated by tabs.
+pub struct Meta {
+    path: PathBuf,
+    entries: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl Meta {
+    pub const EXTENSION: &'static str = "meta";
+
+    /// Activity of the record was capped by the `max_activity` setting
+    pub const CAPPED: &'static str = "capped";
+
+    /// Intended duration of the record activity in minutes, given by `start --for`
+    pub const PLANNED: &'static str = "planned";
+
+    /// Reads the sidecar file of the journal, a missing file means no attributes
+    pub fn load<P: AsRef<Path>>(journal_path: P) -> Self {
+        let mut path = journal_path.as_ref().as_os_str().to_owned();
+        path.push(".");
+        path.push(Self::EXTENSION);
+        let path = PathBuf::from(path);
+
+        let mut meta = Meta { path, entries: BTreeMap::new() };
+        if let Ok(content) = fs::read_to_string(&meta.path) {
+            for line in content.lines() {
+                let fields = line.splitn(3, '\t').collect::<Vec<_>>();
+                if fields.len() == 3 {
+                    meta.set(fields[0], fields[1], fields[2]);
+                }
+            }
+        }
+        meta
+    }
+
+    pub fn get(&self, id: &str, key: &str) -> Option<&str> {
+        self.entries.get(id)
+            .and_then(|attrs| attrs.get(key))
+            .map(|value| value.as_str())
+    }
+
+    pub fn set(&mut self, id: &str, key: &str, value: &str) {
+        self.entries
+            .entry(id.to_string())
+            .or_default()
+            .insert(key.to_string(), value.to_string());
+    }
+
+    /// Removes the attribute, returns whether it was set
+    pub fn remove(&mut self, id: &str, key: &str) -> bool {
+        let removed = self.entries.get_mut(id)
+            .map(|attrs| attrs.remove(key).is_some())
+            .unwrap_or(false);
+        if self.entries.get(id).map(|attrs| attrs.is_empty()).unwrap_or(false) {
+            self.entries.remove(id);
+        }
+        removed
+    }
+
+    /// Moves the attributes to the new identifiers of the records, `None` drops them as the record is gone.
+    /// Returns whether any attributes were moved or dropped
+    pub fn rekey(&mut self, changes: &[(String, Option<String>)]) -> bool {
+        let moved = changes.iter()
+            .filter(|(id, new_id)| new_id.as_ref() != Some(id))
+            .filter_map(|(id, new_id)| self.entries.remove(id).map(|attrs| (new_id, attrs)))
+            .collect::<Vec<_>>();
+        let changed = !moved.is_empty();
+        for (new_id, attrs) in moved {
+            if let Some(new_id) = new_id {
+                self.entries.insert(new_id.clone(), attrs);
+            }
+        }
+        changed
+    }
+
+    pub fn save(&self) {
+        let content = self.entries
+            .iter()
+            .flat_map(|(id, attrs)| attrs.iter().map(move |(key, value)| format!("{}\t{}\t{}\n", id, key, value)))
+            .collect::<String>();
+        fs::write(&self.path, content)
+            .expect(&format!("Can't write record attributes to {:?}", self.path));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+
+    #[test]
+    fn save_and_load_meta() {
+        let journal_path = env::temp_dir().join(format!("tt-meta-test-{}.txt", std::process::id()));
+        let mut meta = Meta::load(&journal_path);
+        assert_eq!(None, meta.get("20181203090000", Meta::CAPPED));
+
+        meta.set("20181203090000", Meta::CAPPED, "600");
+        meta.set("20181203100000", Meta::PLANNED, "45");
+        meta.save();
+
+        let mut meta = Meta::load(&journal_path);
+        assert_eq!(Some("600"), meta.get("20181203090000", Meta::CAPPED));
+        assert_eq!(Some("45"), meta.get("20181203100000", Meta::PLANNED));
+        assert!(meta.remove("20181203090000", Meta::CAPPED));
+        assert!(!meta.remove("20181203090000", Meta::CAPPED));
+        meta.save();
+
+        let meta = Meta::load(&journal_path);
+        assert_eq!(None, meta.get("20181203090000", Meta::CAPPED));
+        fs::remove_file(&meta.path).ok();
+    }
+
+    #[test]
+    fn rekey_meta() {
+        let mut meta = Meta::load(env::temp_dir().join("tt-meta-rekey-test.txt"));
+        meta.set("20181203090000", Meta::CAPPED, "600");
+        meta.set("20181203100000", Meta::PLANNED, "45");
+        meta.set("20181203110000", Meta::PLANNED, "30");
+
+        let changes = vec![
+            ("20181203090000".to_string(), Some("20181203100000".to_string())),
+            ("20181203100000".to_string(), Some("20181203110000".to_string())),
+            ("20181203110000".to_string(), None),
+        ];
+        assert!(meta.rekey(&changes));
+        assert_eq!(None, meta.get("20181203090000", Meta::CAPPED));
+        assert_eq!(Some("600"), meta.get("20181203100000", Meta::CAPPED));
+        assert_eq!(None, meta.get("20181203100000", Meta::PLANNED));
+        assert_eq!(Some("45"), meta.get("20181203110000", Meta::PLANNED));
+        assert!(!meta.rekey(&[("20181203100000".to_string(), Some("20181203100000".to_string()))]));
+    }
+}
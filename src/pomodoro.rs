@@ -0,0 +1,72 @@
+use std::io::{self, Write};
+use std::thread;
+use std::time::{self, Instant};
+use tt_core::record::Duration;
+use crate::cmd::CmdProcessor;
+
+const BAR_WIDTH: usize = 30;
+const BELL: &str = "\x07";
+
+/// Work intervals tracked as journal records, each followed by an untracked break
+pub struct Pomodoro<'a> {
+    processor: &'a mut CmdProcessor,
+    work: Duration,
+    rest: Duration,
+    cycles: u32,
+}
+
+impl<'a> Pomodoro<'a> {
+    pub fn new(processor: &'a mut CmdProcessor, work: Duration, rest: Duration, cycles: u32) -> Self {
+        Pomodoro { processor, work, rest, cycles }
+    }
+
+    pub fn run(&mut self, note: Option<String>) {
+        if self.processor.is_running() {
+            self.processor.stop_record(-1, None);
+        }
+
+        for cycle in 1..=self.cycles {
+            self.processor.start_record(note.clone());
+            count_down(&format!("Work {}/{}", cycle, self.cycles), self.work);
+            self.processor.stop_record(-1, None);
+            ring();
+
+            if self.rest > Duration::zero() {
+                count_down(&format!("Break {}/{}", cycle, self.cycles), self.rest);
+                ring();
+            }
+        }
+    }
+}
+
+/// Shows the progress bar with the time left until the interval ends
+pub fn count_down(label: &str, interval: Duration) {
+    let total = interval.num_seconds().max(0);
+    let started = Instant::now();
+
+    loop {
+        let passed = (started.elapsed().as_secs() as i64).min(total);
+        let filled = if total > 0 {
+            (passed * BAR_WIDTH as i64 / total) as usize
+        } else {
+            BAR_WIDTH
+        };
+        let left = total - passed;
+        print!(
+            "\r{} [{}{}] {:02}:{:02} ",
+            label, "#".repeat(filled), ".".repeat(BAR_WIDTH - filled), left / 60, left % 60
+        );
+        io::stdout().flush().expect("Can't flush stdout");
+
+        if passed >= total {
+            break;
+        }
+        thread::sleep(time::Duration::from_millis(200));
+    }
+    println!();
+}
+
+pub fn ring() {
+    print!("{}", BELL);
+    io::stdout().flush().expect("Can't flush stdout");
+}
@@ -55,7 +55,7 @@ impl ReportNode {
 
     fn to_string_producer(&self, prefix: &str, display_in_hours: bool, root_items_only: bool) -> String {
         let mut string = if display_in_hours {
-            format!("{}{}:{:02}  {}", prefix, self.act / 60, self.act % 60, self.note)
+            format!("{}{}  {}", prefix, hours_minutes(self.act), self.note)
         } else {
             format!("{}{}  {}", prefix, self.act, self.note)
         };
@@ -67,4 +67,10 @@ impl ReportNode {
         }
         string
     }
-}
\ No newline at end of file
+}
+
+/// Formats the minutes as "H:MM", the report in hours, negative minutes as "-H:MM"
+pub fn hours_minutes(minutes: i64) -> String {
+    let sign = if minutes < 0 { "-" } else { "" };
+    format!("{}{}:{:02}", sign, minutes.abs() / 60, minutes.abs() % 60)
+}
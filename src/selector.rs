@@ -0,0 +1,118 @@
+use std::fmt;
+use std::ops::Range;
+use regex::Regex;
+use tt_core::record::{Record, Date, DateTime, Local};
+use tt_core::journal::file::Item;
+use crate::cmd::CmdProcessor;
+
+/// The way of addressing a record in the journal
+pub enum Selector {
+    /// Offset as understood by the journal: negative counts back from the end
+    Offset(i32),
+    /// Range of backward offsets from the last record
+    Range(Range<i32>),
+    /// Date on which the records were started
+    Date(Date<Local>),
+    /// Regular expression that the note of the last selected record should match
+    Match(Regex),
+    /// Regular expression that the notes of all selected records should match
+    Matches(Regex),
+    /// Moment at which the record was running
+    At(DateTime<Local>),
+    /// Record identifier, the start time, see `record_id`
+    Id(String),
+}
+
+impl Selector {
+    pub const ID_FORMAT: &'static str = "%Y%m%d%H%M%S";
+
+    /// Whether the selector addresses a group of records rather than a single one
+    pub fn is_range(&self) -> bool {
+        match self {
+            Selector::Range(_) | Selector::Date(_) | Selector::Matches(_) => true,
+            Selector::Offset(_) | Selector::Match(_) | Selector::At(_) | Selector::Id(_) => false,
+        }
+    }
+
+    /// Returns the journal offset of the most recent record satisfying the selector
+    pub fn find(&self, items: &[Item]) -> Option<i32> {
+        if let Selector::Offset(offset) = *self {
+            return Some(offset);
+        }
+        self.select(items).pop()
+    }
+
+    /// Returns the journal offsets, counted from the first record, of all records satisfying the selector
+    pub fn select(&self, items: &[Item]) -> Vec<i32> {
+        let records = items
+            .iter()
+            .filter_map(|item| match item {
+                Item::Record(record) => Some(record),
+                Item::SomeLine(_) => None,
+            })
+            .collect::<Vec<_>>();
+        let count = records.len() as i32;
+
+        match self {
+            Selector::Offset(offset) => {
+                let index = if *offset < 0 { count + offset } else { *offset };
+                if index >= 0 && index < count {
+                    vec![index]
+                } else {
+                    vec![]
+                }
+            },
+            Selector::Range(range) => {
+                let mut offsets = (range.start.max(0)..range.end.min(count))
+                    .map(|n| count - n - 1)
+                    .collect::<Vec<_>>();
+                offsets.sort();
+                offsets
+            },
+            _ => records
+                .iter()
+                .enumerate()
+                .filter(|(_, record)| self.is_match(record))
+                .map(|(index, _)| index as i32)
+                .collect(),
+        }
+    }
+
+    pub fn is_match(&self, record: &Record) -> bool {
+        match self {
+            Selector::Offset(_) | Selector::Range(_) => false,
+            Selector::Date(date) => record.start.map(|start| start.date() == *date).unwrap_or(false),
+            Selector::Match(regex) | Selector::Matches(regex) => regex.is_match(&record.note),
+            Selector::At(at) => record.start
+                .map(|start| start <= *at && CmdProcessor::end_of(record).map(|end| end > *at).unwrap_or(true))
+                .unwrap_or(false),
+            Selector::Id(id) => record_id(record).map(|record_id| &record_id == id).unwrap_or(false),
+        }
+    }
+}
+
+impl fmt::Display for Selector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Selector::Offset(offset) => write!(f, "offset {}", offset),
+            Selector::Range(range) => write!(f, "offsets {}..{}", range.start, range.end),
+            Selector::Date(date) => write!(f, "date {}", date.format("%Y-%m-%d")),
+            Selector::Match(regex) => write!(f, "match {:?}", regex.as_str()),
+            Selector::Matches(regex) => write!(f, "matches {:?}", regex.as_str()),
+            Selector::At(at) => write!(f, "time {}", at.format(Record::START_DATETIME_FORMAT)),
+            Selector::Id(id) => write!(f, "id {}", id),
+        }
+    }
+}
+
+/// Records addressed by a command: a single one by journal offset or a group by offsets from the first record
+pub enum Target {
+    One(i32),
+    Many(Vec<i32>),
+}
+
+/// Record identifier: the start time to the second. It follows the start, so it changes when the start is edited,
+/// and records started at the same second share it
+pub fn record_id(record: &Record) -> Option<String> {
+    record.start.map(|start| start.format(Selector::ID_FORMAT).to_string())
+}
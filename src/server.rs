@@ -0,0 +1,311 @@
+use std::collections::HashMap;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::panic::{self, AssertUnwindSafe};
+use std::time;
+use serde::{Serialize, Deserialize};
+use serde_json::json;
+use tt_core::record::{Record, Date, Local};
+use crate::cmd::CmdProcessor;
+use crate::report::ReportNode;
+
+const MAX_BODY_SIZE: usize = 64 * 1024;
+const MAX_HEADERS_SIZE: usize = 16 * 1024;
+
+/// Body of the start and stop requests
+#[derive(Default, Deserialize)]
+struct NoteBody {
+    note: Option<String>,
+}
+
+#[derive(Serialize)]
+struct RecordJson {
+    start: Option<String>,
+    /// Minutes of activity, none for the running record
+    activity: Option<i64>,
+    rest: Option<i64>,
+    note: String,
+    line: String,
+}
+
+#[derive(Serialize)]
+struct NodeJson {
+    note: String,
+    activity: i64,
+    children: Vec<NodeJson>,
+}
+
+struct Request {
+    method: String,
+    path: String,
+    query: HashMap<String, String>,
+    /// Header values by the lowercased names
+    headers: HashMap<String, String>,
+    body: String,
+}
+
+struct Response {
+    status: u16,
+    body: serde_json::Value,
+}
+
+impl Response {
+    fn ok(body: serde_json::Value) -> Self {
+        Response { status: 200, body }
+    }
+
+    fn error(status: u16, message: &str) -> Self {
+        Response { status, body: json!({ "error": message }) }
+    }
+}
+
+/// JSON API over the command processor, listening on localhost only.
+///
+/// `GET /status`, `POST /start`, `POST /stop`, `POST /restart`, `GET /records?from=&to=`, `GET /report?from=&to=`,
+/// the dates are in the format of the command line and default to today.
+///
+/// Against cross-site requests and DNS rebinding, the server answers only requests with the Host of its own address
+/// and without Origin, which browsers send from web pages, and takes POST bodies only of the JSON content type.
+pub struct Server<'a> {
+    processor: &'a mut CmdProcessor,
+    port: u16,
+}
+
+impl<'a> Server<'a> {
+    pub fn new(processor: &'a mut CmdProcessor, port: u16) -> Self {
+        Server { processor, port }
+    }
+
+    pub fn run(&mut self) -> io::Result<()> {
+        let listener = TcpListener::bind(("127.0.0.1", self.port))?;
+        // The port 0 is replaced by the one chosen by the system, which the Host of requests should have
+        self.port = listener.local_addr()?.port();
+        println!("Listening on http://{}", listener.local_addr()?);
+
+        for stream in listener.incoming() {
+            if let Err(err) = stream.and_then(|stream| self.serve(stream)) {
+                eprintln!("Can't serve request: {}", err);
+            }
+        }
+        Ok(())
+    }
+
+    fn serve(&mut self, mut stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(time::Duration::from_secs(5)))?;
+        let response = match read_request(&mut stream)? {
+            Some(request) => if let Some(response) = check(&request, self.port) {
+                response
+            } else {
+                let processor = &mut *self.processor;
+                panic::catch_unwind(AssertUnwindSafe(|| route(processor, &request)))
+                    .unwrap_or_else(|err| {
+                        let message = err.downcast_ref::<String>()
+                            .map(|message| message.as_str())
+                            .or_else(|| err.downcast_ref::<&str>().cloned())
+                            .unwrap_or("Internal error");
+                        Response::error(500, message)
+                    })
+            },
+            None => Response::error(400, "Bad request"),
+        };
+
+        let body = response.body.to_string();
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            response.status, reason(response.status), body.len(), body
+        )?;
+        stream.flush()
+    }
+}
+
+/// Returns the error response to the request that doesn't come from a local client of the API
+fn check(request: &Request, port: u16) -> Option<Response> {
+    let host = request.headers.get("host").map(|host| host.to_lowercase());
+    let hosts = [format!("127.0.0.1:{}", port), format!("localhost:{}", port)];
+    if host.map(|host| !hosts.contains(&host)).unwrap_or(true) {
+        return Some(Response::error(403, "Host is not allowed"));
+    }
+    if request.headers.contains_key("origin") {
+        return Some(Response::error(403, "Cross-origin requests are not allowed"));
+    }
+    let json = request.headers.get("content-type")
+        .and_then(|content_type| content_type.split(';').next())
+        .map(|media_type| media_type.trim().eq_ignore_ascii_case("application/json"))
+        .unwrap_or(false);
+    if request.method == "POST" && !json {
+        return Some(Response::error(415, "Content-Type must be application/json"));
+    }
+    None
+}
+
+fn route(processor: &mut CmdProcessor, request: &Request) -> Response {
+    match (request.method.as_str(), request.path.as_str()) {
+        ("GET", "/status") => Response::ok(status(processor)),
+        ("POST", "/start") => {
+            let body = match parse_body(&request.body) {
+                Some(body) => body,
+                None => return Response::error(400, "Invalid JSON body"),
+            };
+            if processor.is_running() {
+                processor.stop_record(-1, None);
+            }
+            processor.start_record(body.note);
+            Response::ok(status(processor))
+        },
+        ("POST", "/stop") => {
+            let body = match parse_body(&request.body) {
+                Some(body) => body,
+                None => return Response::error(400, "Invalid JSON body"),
+            };
+            if !processor.is_running() {
+                return Response::error(409, "Nothing is running");
+            }
+            processor.stop_record(-1, body.note);
+            Response::ok(status(processor))
+        },
+        ("POST", "/restart") => {
+            if processor.is_running() || processor.last_record().is_none() {
+                return Response::error(409, "Nothing to restart");
+            }
+            processor.restart_record(-1, None);
+            Response::ok(status(processor))
+        },
+        ("GET", "/records") => {
+            let (from, to) = match dates(request) {
+                Ok((from, to)) => (Some(from), Some(to)),
+                Err(message) => return Response::error(400, &message),
+            };
+            let records = processor.records_between(from, to).iter().map(record_json).collect::<Vec<_>>();
+            Response::ok(json!({ "records": records }))
+        },
+        ("GET", "/report") => {
+            let (from, to) = match dates(request) {
+                Ok((from, to)) => (Some(from), Some(to)),
+                Err(message) => return Response::error(400, &message),
+            };
+            let (nodes, total) = processor.report_nodes(from, to);
+            Response::ok(json!({ "nodes": nodes.iter().map(node_json).collect::<Vec<_>>(), "total": total }))
+        },
+        (_, "/status") | (_, "/start") | (_, "/stop") | (_, "/restart") | (_, "/records") | (_, "/report") => {
+            Response::error(405, "Method not allowed")
+        },
+        _ => Response::error(404, "Not found"),
+    }
+}
+
+fn status(processor: &CmdProcessor) -> serde_json::Value {
+    match processor.last_record() {
+        Some(ref record) if record.start.is_some() && record.activity.is_none() => json!({
+            "running": true,
+            "record": record_json(record),
+            "elapsed": CmdProcessor::elapsed(record).map(|elapsed| elapsed.num_minutes()),
+        }),
+        _ => json!({ "running": false, "record": null, "elapsed": null }),
+    }
+}
+
+fn record_json(record: &Record) -> RecordJson {
+    RecordJson {
+        start: record.start.map(|start| start.format(Record::START_DATETIME_FORMAT).to_string()),
+        activity: record.activity.map(|activity| activity.num_minutes()),
+        rest: record.rest.map(|rest| rest.num_minutes()),
+        note: record.note.clone(),
+        line: record.to_string(),
+    }
+}
+
+fn node_json(node: &ReportNode) -> NodeJson {
+    NodeJson {
+        note: node.note.clone(),
+        activity: node.act,
+        children: node.children.iter().map(node_json).collect(),
+    }
+}
+
+/// The range of the `from` and `to` query dates, `to` defaults to `from` and `from` to today
+fn dates(request: &Request) -> Result<(Date<Local>, Date<Local>), String> {
+    let date = |name: &str| match request.query.get(name) {
+        Some(text) => CmdProcessor::try_parse_date(text).map(Some).ok_or_else(|| format!("Invalid date {}={}", name, text)),
+        None => Ok(None),
+    };
+    let from = date("from")?.unwrap_or_else(|| Local::now().date());
+    let to = date("to")?.unwrap_or(from);
+    if to < from {
+        return Err("The date to is earlier than from".to_string());
+    }
+    Ok((from, to))
+}
+
+fn parse_body(body: &str) -> Option<NoteBody> {
+    if body.trim().is_empty() {
+        Some(NoteBody::default())
+    } else {
+        serde_json::from_str(body).ok()
+    }
+}
+
+/// Reads the request line, the headers and the body of the Content-Length size, the requests with the headers or
+/// the body over the limits are bad
+fn read_request(stream: &mut TcpStream) -> io::Result<Option<Request>> {
+    let mut reader = BufReader::new(stream).take(MAX_HEADERS_SIZE as u64);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+    let parts = line.split_whitespace().collect::<Vec<_>>();
+    let (method, target) = match parts.as_slice() {
+        [method, target, _] => (method.to_string(), target.to_string()),
+        _ => return Ok(None),
+    };
+
+    let mut headers = HashMap::new();
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+        let mut parts = header.splitn(2, ':');
+        if let (Some(name), Some(value)) = (parts.next(), parts.next()) {
+            headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+        }
+    }
+    if reader.limit() == 0 {
+        return Ok(None);
+    }
+    let mut reader = reader.into_inner();
+    let length = headers.get("content-length")
+        .map(|length| length.parse::<usize>().unwrap_or(0))
+        .unwrap_or(0);
+    if length > MAX_BODY_SIZE {
+        return Ok(None);
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body)?;
+
+    let mut target = target.splitn(2, '?');
+    let path = target.next().unwrap_or_default().to_string();
+    let query = target.next()
+        .map(|query| {
+            query.split('&')
+                .filter_map(|pair| {
+                    let mut pair = pair.splitn(2, '=');
+                    Some((pair.next()?.to_string(), pair.next().unwrap_or_default().to_string()))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(Some(Request { method, path, query, headers, body: String::from_utf8_lossy(&body).to_string() }))
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        415 => "Unsupported Media Type",
+        _ => "Internal Server Error",
+    }
+}
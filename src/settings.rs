@@ -1,12 +1,39 @@
+use std::collections::HashMap;
 use std::env;
 use std::path::PathBuf;
 use serde::{Serialize, Deserialize};
 use config::{Config, ConfigError, Environment, File};
 
+/// What to do when an edited record start makes it overlap a neighbouring record
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Overlap {
+    Refuse,
+    Warn,
+    Allow,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct Settings {
     pub journal_file: String,
     pub print: bool,
+    pub overlap: Overlap,
+    /// Minutes of running after which stop asks how to book the elapsed time, 0 disables the check
+    pub idle_threshold: i64,
+    /// Minutes of activity to which forgotten running records are capped, 0 disables the cap
+    pub max_activity: i64,
+    /// Minutes of the pomodoro work interval
+    pub pomodoro_work: i64,
+    /// Minutes of the pomodoro break, 0 disables breaks
+    pub pomodoro_break: i64,
+    /// Short names of frequent notes, see `alias::expand`
+    #[serde(default)]
+    pub aliases: HashMap<String, String>,
+    /// Note prefixes of calendar events whose summary contains the key, see `import::calendar_records`
+    #[serde(default)]
+    pub calendar_notes: HashMap<String, String>,
+    /// Note of the record started on branch checkout, see `git::branch_note`
+    pub branch_note_template: String,
 }
 
 impl Default for Settings {
@@ -14,6 +41,14 @@ impl Default for Settings {
         Settings {
             journal_file: "journal.txt".to_string(),
             print: true,
+            overlap: Overlap::Refuse,
+            idle_threshold: 0,
+            max_activity: 0,
+            pomodoro_work: 25,
+            pomodoro_break: 5,
+            aliases: HashMap::new(),
+            calendar_notes: HashMap::new(),
+            branch_note_template: "{ticket} {title}".to_string(),
         }
     }
 }
@@ -53,5 +88,8 @@ mod tests {
         env::remove_var("TT_CLI_HOME");
         let settings = Settings::new().unwrap();
         assert_eq!("journal.txt", &settings.journal_file);
+        assert_eq!(Overlap::Refuse, settings.overlap);
+        assert!(settings.aliases.is_empty());
+        assert!(settings.calendar_notes.is_empty());
     }
 }
\ No newline at end of file
@@ -0,0 +1,196 @@
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+use tt_core::record::{Local, Date, Datelike, Duration, TimeZone};
+use crate::report::hours_minutes;
+
+const TOP_PROJECTS: usize = 5;
+
+/// Period of the summary
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Period {
+    /// Monday to Sunday, with a row for each day
+    Week,
+    /// Calendar month, with a row for each week
+    Month,
+}
+
+impl Period {
+    /// First and last dates of the period containing the date
+    pub fn range(self, date: Date<Local>) -> (Date<Local>, Date<Local>) {
+        match self {
+            Period::Week => {
+                let first = date - Duration::days(i64::from(date.weekday().num_days_from_monday()));
+                (first, first + Duration::days(6))
+            },
+            Period::Month => {
+                let first = Local.ymd(date.year(), date.month(), 1);
+                let next = if date.month() == 12 {
+                    Local.ymd(date.year() + 1, 1, 1)
+                } else {
+                    Local.ymd(date.year(), date.month() + 1, 1)
+                };
+                (first, next.pred())
+            },
+        }
+    }
+
+    /// First and last dates of the period before the one containing the date
+    pub fn previous(self, date: Date<Local>) -> (Date<Local>, Date<Local>) {
+        self.range(self.range(date).0.pred())
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Period::Week => "week",
+            Period::Month => "month",
+        }
+    }
+}
+
+/// Minutes of activity of a record started on the date
+pub struct Entry {
+    pub date: Date<Local>,
+    pub note: String,
+    pub minutes: i64,
+}
+
+/// Builds the summary table of the period containing the date: totals of days (of weeks for the month),
+/// the average of days with activity, the top projects and the change from the previous period.
+///
+/// While the period goes on, it is compared with the same number of the first days of the previous period,
+/// up to today. As in the report, the project is the first word of the note.
+pub fn summary(period: Period, date: Date<Local>, today: Date<Local>, entries: &[Entry]) -> Vec<String> {
+    let (first, last) = period.range(date);
+    let (previous_first, mut previous_last) = period.previous(date);
+    let partial = first <= today && today < last;
+    if partial {
+        previous_last = previous_last.min(previous_first + (today - first));
+    }
+    let in_range = |entry: &&Entry, first: Date<Local>, last: Date<Local>| entry.date >= first && entry.date <= last;
+    let current = entries.iter().filter(|entry| in_range(entry, first, last)).collect::<Vec<_>>();
+    let previous_total = entries.iter()
+        .filter(|entry| in_range(entry, previous_first, previous_last))
+        .map(|entry| entry.minutes)
+        .sum::<i64>();
+
+    let mut days = BTreeMap::new();
+    let mut projects = BTreeMap::new();
+    for entry in current.iter() {
+        *days.entry(entry.date).or_insert(0) += entry.minutes;
+        let project = entry.note.split_whitespace().next().unwrap_or_default().to_string();
+        *projects.entry(project).or_insert(0) += entry.minutes;
+    }
+    let total = days.values().sum::<i64>();
+
+    let mut lines = vec![format!("{} - {}", first.format("%Y-%m-%d"), last.format("%Y-%m-%d"))];
+    let mut day = first;
+    while day <= last {
+        let (label, row_last) = match period {
+            Period::Week => (day.format("%a %Y-%m-%d").to_string(), day),
+            Period::Month => {
+                let (_, week_last) = Period::Week.range(day);
+                let week_last = if week_last > last { last } else { week_last };
+                (format!("{} - {}", day.format("%m-%d"), week_last.format("%m-%d")), week_last)
+            },
+        };
+        let minutes = days.range(day..=row_last).map(|(_, minutes)| minutes).sum::<i64>();
+        lines.push(format!("{:<14}  {:>6}", label, hours_minutes(minutes)));
+        day = row_last.succ();
+    }
+
+    lines.push("-".repeat(22));
+    lines.push(format!("Total {:>16}", hours_minutes(total)));
+    let active_days = days.values().filter(|minutes| **minutes > 0).count() as i64;
+    if active_days > 0 {
+        let days = if active_days == 1 { "day" } else { "days" };
+        lines.push(format!("Average {:>14} per day of {} {} with activity", hours_minutes(total / active_days), active_days, days));
+    }
+
+    let mut projects = projects.into_iter().collect::<Vec<_>>();
+    projects.sort_by_key(|(project, minutes)| (Reverse(*minutes), project.clone()));
+    if !projects.is_empty() {
+        lines.push("Top projects:".to_string());
+        for (project, minutes) in projects.iter().take(TOP_PROJECTS) {
+            lines.push(format!("  {:>6}  {}", hours_minutes(*minutes), project));
+        }
+    }
+
+    let delta = total - previous_total;
+    let percent = if previous_total > 0 {
+        format!("{:+.1}%", delta as f64 * 100.0 / previous_total as f64)
+    } else {
+        "n/a".to_string()
+    };
+    let sign = if delta < 0 { "" } else { "+" };
+    let until = if partial { format!(" until {}", previous_last.format("%Y-%m-%d")) } else { String::new() };
+    lines.push(format!(
+        "Previous {}{} {}, {}{} ({:+} minutes, {})",
+        period.name(), until, hours_minutes(previous_total), sign, hours_minutes(delta), delta, percent
+    ));
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(day: u32, note: &str, minutes: i64) -> Entry {
+        Entry { date: Local.ymd(2018, 12, day), note: note.to_string(), minutes }
+    }
+
+    #[test]
+    fn period_ranges() {
+        let date = Local.ymd(2018, 12, 5);
+        assert_eq!((Local.ymd(2018, 12, 3), Local.ymd(2018, 12, 9)), Period::Week.range(date));
+        assert_eq!((Local.ymd(2018, 11, 26), Local.ymd(2018, 12, 2)), Period::Week.previous(date));
+        assert_eq!((Local.ymd(2018, 12, 1), Local.ymd(2018, 12, 31)), Period::Month.range(date));
+        assert_eq!((Local.ymd(2018, 11, 1), Local.ymd(2018, 11, 30)), Period::Month.previous(date));
+    }
+
+    #[test]
+    fn week_summary() {
+        let entries = vec![
+            entry(1, "Day work task0", 120),
+            entry(3, "Day work task1", 300),
+            entry(3, "Meetings standup", 15),
+            entry(4, "Day work task2", 240),
+        ];
+
+        let lines = summary(Period::Week, Local.ymd(2018, 12, 5), Local.ymd(2019, 1, 1), &entries);
+        assert_eq!("2018-12-03 - 2018-12-09", lines[0]);
+        assert_eq!("Mon 2018-12-03    5:15", lines[1]);
+        assert_eq!("Tue 2018-12-04    4:00", lines[2]);
+        assert_eq!("Total             9:15", lines[9]);
+        assert_eq!("Average           4:37 per day of 2 days with activity", lines[10]);
+        assert_eq!("    9:00  Day", lines[12]);
+        assert_eq!("    0:15  Meetings", lines[13]);
+        assert_eq!("Previous week 2:00, +7:15 (+435 minutes, +362.5%)", lines[14]);
+    }
+
+    #[test]
+    fn month_summary() {
+        let lines = summary(Period::Month, Local.ymd(2018, 12, 5), Local.ymd(2019, 1, 1), &[entry(3, "Day work task1", 60)]);
+        assert_eq!("12-01 - 12-02     0:00", lines[1]);
+        assert_eq!("12-03 - 12-09     1:00", lines[2]);
+        assert_eq!("12-31 - 12-31     0:00", lines[6]);
+        assert_eq!("Average           1:00 per day of 1 day with activity", lines[9]);
+        assert_eq!("Previous month 0:00, +1:00 (+60 minutes, n/a)", lines[12]);
+    }
+
+    #[test]
+    fn partial_week_summary() {
+        let entries = vec![
+            entry(1, "Day work task0", 120),
+            entry(3, "Day work task1", 300),
+            entry(4, "Day work task2", 240),
+            entry(6, "Day work task3", 60),
+        ];
+
+        let lines = summary(Period::Week, Local.ymd(2018, 12, 11), Local.ymd(2018, 12, 11), &entries);
+        assert_eq!("Previous week until 2018-12-04 9:00, -9:00 (-540 minutes, -100.0%)", lines[10]);
+        assert_eq!(lines[10], summary(Period::Week, Local.ymd(2018, 12, 10), Local.ymd(2018, 12, 11), &entries)[10]);
+
+        let lines = summary(Period::Week, Local.ymd(2018, 12, 5), Local.ymd(2018, 12, 11), &entries);
+        assert_eq!("Previous week 2:00, +8:00 (+480 minutes, +400.0%)", lines[13]);
+    }
+}
@@ -0,0 +1,57 @@
+use std::env;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, ErrorKind, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Number of names tried for the temporary file before giving up
+const TEMP_ATTEMPTS: u32 = 100;
+
+/// Temporary file readable only by the user, removed when dropped
+pub struct TempFile {
+    pub path: PathBuf,
+}
+
+impl TempFile {
+    /// Creates a new file with an unpredictable name, never opening an existing file or following a symlink.
+    /// The file is put in the user runtime directory when there is one, otherwise in the system temporary directory
+    pub fn create(prefix: &str, content: &[u8]) -> io::Result<Self> {
+        let dir = env::var_os("XDG_RUNTIME_DIR")
+            .map(PathBuf::from)
+            .filter(|dir| dir.is_dir())
+            .unwrap_or_else(env::temp_dir);
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|time| time.subsec_nanos()).unwrap_or(0);
+        for attempt in 0..TEMP_ATTEMPTS {
+            let path = dir.join(format!("{}-{}-{}.txt", prefix, process::id(), nanos.wrapping_add(attempt)));
+            match open_private(&path) {
+                Ok(mut file) => {
+                    let temp = TempFile { path };
+                    file.write_all(content)?;
+                    return Ok(temp);
+                },
+                Err(ref err) if err.kind() == ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(err),
+            }
+        }
+        Err(io::Error::new(ErrorKind::AlreadyExists, "Can't find a free temporary file name"))
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        fs::remove_file(&self.path).ok();
+    }
+}
+
+#[cfg(unix)]
+fn open_private(path: &Path) -> io::Result<File> {
+    use std::os::unix::fs::OpenOptionsExt;
+
+    OpenOptions::new().write(true).create_new(true).mode(0o600).open(path)
+}
+
+#[cfg(not(unix))]
+fn open_private(path: &Path) -> io::Result<File> {
+    OpenOptions::new().write(true).create_new(true).open(path)
+}
@@ -0,0 +1,274 @@
+use std::collections::BTreeMap;
+use tt_core::record::{Record, Duration};
+use crate::cmd::CmdProcessor;
+use crate::zip::ZipWriter;
+
+/// Rows of the timesheet
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Grouping {
+    /// A row for each record
+    Record,
+    /// A row for each project of each day
+    Day,
+}
+
+impl Grouping {
+    pub const NAMES: [&'static str; 2] = ["record", "day"];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "record" => Some(Grouping::Record),
+            "day" => Some(Grouping::Day),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Cell {
+    Empty,
+    Text(String),
+    /// Duration in minutes
+    Number(i64),
+}
+
+const HEADER: [&str; 7] = ["date", "start", "end", "activity", "rest", "project", "task"];
+
+/// Builds the timesheet of the stopped records with the header and the totals row.
+///
+/// As in the report, the project is the first word of the note and the task is the rest of it.
+pub fn rows(records: &[Record], grouping: Grouping) -> Vec<Vec<Cell>> {
+    let text = |text: &str| if text.is_empty() { Cell::Empty } else { Cell::Text(text.to_string()) };
+    let mut rows = vec![HEADER.iter().map(|name| text(name)).collect::<Vec<_>>()];
+    let mut days = BTreeMap::new();
+    let (mut total_activity, mut total_rest) = (0, 0);
+
+    for record in records {
+        let (start, end) = match (record.start, CmdProcessor::end_of(record)) {
+            (Some(start), Some(end)) => (start, end),
+            _ => continue,
+        };
+        let activity = record.activity.unwrap_or_else(Duration::zero).num_minutes();
+        let rest = record.rest.unwrap_or_else(Duration::zero).num_minutes();
+        let mut words = record.note.split_whitespace();
+        let project = words.next().unwrap_or_default().to_string();
+        let task = words.collect::<Vec<_>>().join(" ");
+        let date = start.format("%Y-%m-%d").to_string();
+        total_activity += activity;
+        total_rest += rest;
+
+        match grouping {
+            Grouping::Record => rows.push(vec![
+                text(&date),
+                text(&start.format("%H:%M").to_string()),
+                text(&end.format("%H:%M").to_string()),
+                Cell::Number(activity),
+                Cell::Number(rest),
+                text(&project),
+                text(&task),
+            ]),
+            Grouping::Day => {
+                let sums = days.entry((date, project)).or_insert((0, 0));
+                sums.0 += activity;
+                sums.1 += rest;
+            },
+        }
+    }
+
+    for ((date, project), (activity, rest)) in days {
+        rows.push(vec![
+            text(&date), Cell::Empty, Cell::Empty, Cell::Number(activity), Cell::Number(rest), text(&project), Cell::Empty,
+        ]);
+    }
+    rows.push(vec![
+        text("Total"), Cell::Empty, Cell::Empty, Cell::Number(total_activity), Cell::Number(total_rest), Cell::Empty, Cell::Empty,
+    ]);
+    rows
+}
+
+pub fn to_csv(rows: &[Vec<Cell>]) -> String {
+    rows.iter()
+        .map(|row| {
+            let values = row.iter()
+                .map(|cell| match cell {
+                    Cell::Empty => String::new(),
+                    Cell::Number(number) => number.to_string(),
+                    Cell::Text(text) if text.contains(&[',', '"', '\n'][..]) => {
+                        format!("\"{}\"", text.replace('"', "\"\""))
+                    },
+                    Cell::Text(text) => text.clone(),
+                })
+                .collect::<Vec<_>>();
+            format!("{}\n", values.join(","))
+        })
+        .collect()
+}
+
+/// Writes the Office Open XML workbook with the single sheet of inline strings
+pub fn to_xlsx(rows: &[Vec<Cell>]) -> Vec<u8> {
+    let mut sheet = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+        <worksheet xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\"><sheetData>"
+    );
+    for (row_index, row) in rows.iter().enumerate() {
+        sheet.push_str(&format!("<row r=\"{}\">", row_index + 1));
+        for (column_index, cell) in row.iter().enumerate() {
+            let reference = format!("{}{}", column_name(column_index), row_index + 1);
+            match cell {
+                Cell::Empty => (),
+                Cell::Number(number) => sheet.push_str(&format!("<c r=\"{}\"><v>{}</v></c>", reference, number)),
+                Cell::Text(text) => sheet.push_str(&format!(
+                    "<c r=\"{}\" t=\"inlineStr\"><is><t>{}</t></is></c>", reference, escape_xml(text)
+                )),
+            }
+        }
+        sheet.push_str("</row>");
+    }
+    sheet.push_str("</sheetData></worksheet>");
+
+    let mut zip = ZipWriter::new();
+    zip.add("[Content_Types].xml", XLSX_CONTENT_TYPES.as_bytes());
+    zip.add("_rels/.rels", XLSX_RELS.as_bytes());
+    zip.add("xl/workbook.xml", XLSX_WORKBOOK.as_bytes());
+    zip.add("xl/_rels/workbook.xml.rels", XLSX_WORKBOOK_RELS.as_bytes());
+    zip.add("xl/worksheets/sheet1.xml", sheet.as_bytes());
+    zip.finish()
+}
+
+/// Writes the OpenDocument spreadsheet with the single table
+pub fn to_ods(rows: &[Vec<Cell>]) -> Vec<u8> {
+    let mut content = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+        <office:document-content xmlns:office=\"urn:oasis:names:tc:opendocument:xmlns:office:1.0\" \
+        xmlns:table=\"urn:oasis:names:tc:opendocument:xmlns:table:1.0\" \
+        xmlns:text=\"urn:oasis:names:tc:opendocument:xmlns:text:1.0\" office:version=\"1.2\">\
+        <office:body><office:spreadsheet><table:table table:name=\"Timesheet\">"
+    );
+    for row in rows {
+        content.push_str("<table:table-row>");
+        for cell in row {
+            match cell {
+                Cell::Empty => content.push_str("<table:table-cell/>"),
+                Cell::Number(number) => content.push_str(&format!(
+                    "<table:table-cell office:value-type=\"float\" office:value=\"{0}\"><text:p>{0}</text:p></table:table-cell>",
+                    number
+                )),
+                Cell::Text(text) => content.push_str(&format!(
+                    "<table:table-cell office:value-type=\"string\"><text:p>{}</text:p></table:table-cell>",
+                    escape_xml(text)
+                )),
+            }
+        }
+        content.push_str("</table:table-row>");
+    }
+    content.push_str("</table:table></office:spreadsheet></office:body></office:document-content>");
+
+    let mut zip = ZipWriter::new();
+    zip.add("mimetype", ODS_MIME_TYPE.as_bytes());
+    zip.add("META-INF/manifest.xml", ODS_MANIFEST.as_bytes());
+    zip.add("content.xml", content.as_bytes());
+    zip.finish()
+}
+
+/// Column letters of the spreadsheet: "A", "B", ..., "Z", "AA"...
+fn column_name(index: usize) -> String {
+    let mut name = String::new();
+    let mut index = index + 1;
+    while index > 0 {
+        name.insert(0, (b'A' + ((index - 1) % 26) as u8) as char);
+        index = (index - 1) / 26;
+    }
+    name
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+const XLSX_CONTENT_TYPES: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+    <Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\
+    <Default Extension=\"rels\" ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\
+    <Default Extension=\"xml\" ContentType=\"application/xml\"/>\
+    <Override PartName=\"/xl/workbook.xml\" \
+    ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml\"/>\
+    <Override PartName=\"/xl/worksheets/sheet1.xml\" \
+    ContentType=\"application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml\"/>\
+    </Types>";
+
+const XLSX_RELS: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+    <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+    <Relationship Id=\"rId1\" \
+    Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument\" \
+    Target=\"xl/workbook.xml\"/>\
+    </Relationships>";
+
+const XLSX_WORKBOOK: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+    <workbook xmlns=\"http://schemas.openxmlformats.org/spreadsheetml/2006/main\" \
+    xmlns:r=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships\">\
+    <sheets><sheet name=\"Timesheet\" sheetId=\"1\" r:id=\"rId1\"/></sheets>\
+    </workbook>";
+
+const XLSX_WORKBOOK_RELS: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n\
+    <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\
+    <Relationship Id=\"rId1\" \
+    Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet\" \
+    Target=\"worksheets/sheet1.xml\"/>\
+    </Relationships>";
+
+const ODS_MIME_TYPE: &str = "application/vnd.oasis.opendocument.spreadsheet";
+
+const ODS_MANIFEST: &str = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+    <manifest:manifest xmlns:manifest=\"urn:oasis:names:tc:opendocument:xmlns:manifest:1.0\" manifest:version=\"1.2\">\
+    <manifest:file-entry manifest:full-path=\"/\" manifest:version=\"1.2\" \
+    manifest:media-type=\"application/vnd.oasis.opendocument.spreadsheet\"/>\
+    <manifest:file-entry manifest:full-path=\"content.xml\" manifest:media-type=\"text/xml\"/>\
+    </manifest:manifest>";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tt_core::record::{Local, TimeZone};
+
+    fn record(hour: u32, activity: i64, note: &str) -> Record {
+        let mut record = Record::now();
+        record.start = Some(Local.ymd(2018, 12, 3).and_hms(hour, 0, 0));
+        record.activity = Some(Duration::minutes(activity));
+        record.rest = None;
+        record.note = note.to_string();
+        record
+    }
+
+    #[test]
+    fn timesheet_rows() {
+        let records = vec![
+            record(9, 60, "Day work task1"),
+            record(10, 30, "Meetings, standup"),
+            record(11, 45, "Day work task2"),
+        ];
+
+        let csv = to_csv(&rows(&records, Grouping::Record));
+        assert_eq!("\
+            date,start,end,activity,rest,project,task\n\
+            2018-12-03,09:00,10:00,60,0,Day,work task1\n\
+            2018-12-03,10:00,10:30,30,0,\"Meetings,\",standup\n\
+            2018-12-03,11:00,11:45,45,0,Day,work task2\n\
+            Total,,,135,0,,\n", csv);
+
+        let csv = to_csv(&rows(&records, Grouping::Day));
+        assert_eq!("\
+            date,start,end,activity,rest,project,task\n\
+            2018-12-03,,,105,0,Day,\n\
+            2018-12-03,,,30,0,\"Meetings,\",\n\
+            Total,,,135,0,,\n", csv);
+    }
+
+    #[test]
+    fn spreadsheet_column_names() {
+        assert_eq!("A", column_name(0));
+        assert_eq!("Z", column_name(25));
+        assert_eq!("AA", column_name(26));
+    }
+}
@@ -0,0 +1,254 @@
+use std::io::{self, Stdout, Write};
+use std::thread;
+use std::time::{self, Instant};
+use termion::{clear, cursor, style};
+use termion::event::Key;
+use termion::input::TermRead;
+use termion::raw::{IntoRawMode, RawTerminal};
+use termion::screen::AlternateScreen;
+use tt_core::record::{Local, Date, Duration};
+use crate::cmd::CmdProcessor;
+
+const HELP: &str = "[←/→] day  [.] today  [s] start  [t] stop  [r] restart  [n] note  [c] collapse  [q] quit";
+
+enum Action {
+    Start,
+    EditNote,
+}
+
+struct Prompt {
+    action: Action,
+    text: String,
+}
+
+/// The terminal in raw mode on the alternate screen with the cursor hidden, restored when dropped, also by a panic
+struct Screen(AlternateScreen<RawTerminal<Stdout>>);
+
+impl Screen {
+    fn open() -> io::Result<Self> {
+        let mut screen = AlternateScreen::from(io::stdout().into_raw_mode()?);
+        write!(screen, "{}", cursor::Hide)?;
+        Ok(Screen(screen))
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        // The raw mode and the alternate screen are left by the drops of the wrapped terminal
+        write!(self.0, "{}", cursor::Show).ok();
+        self.0.flush().ok();
+    }
+}
+
+/// Full-screen terminal interface over the command processor
+pub struct Tui<'a> {
+    processor: &'a mut CmdProcessor,
+    date: Date<Local>,
+    root_items_only: bool,
+    prompt: Option<Prompt>,
+    message: String,
+}
+
+impl<'a> Tui<'a> {
+    pub fn new(processor: &'a mut CmdProcessor) -> Self {
+        Tui {
+            processor,
+            date: Local::now().date(),
+            root_items_only: false,
+            prompt: None,
+            message: String::new(),
+        }
+    }
+
+    pub fn run(&mut self) -> io::Result<()> {
+        let mut screen = Screen::open()?;
+        let mut keys = termion::async_stdin().keys();
+
+        loop {
+            self.draw(&mut screen.0)?;
+
+            let deadline = Instant::now() + time::Duration::from_secs(1);
+            let mut redraw = false;
+            while !redraw && Instant::now() < deadline {
+                match keys.next() {
+                    Some(Ok(key)) => {
+                        if !self.handle(key) {
+                            return Ok(());
+                        }
+                        redraw = true;
+                    },
+                    _ => thread::sleep(time::Duration::from_millis(50)),
+                }
+            }
+        }
+    }
+
+    /// Handles the key press, returns false when the interface should be closed
+    fn handle(&mut self, key: Key) -> bool {
+        if let Some(mut prompt) = self.prompt.take() {
+            match key {
+                Key::Char('\n') => self.submit(prompt),
+                Key::Esc => self.message.clear(),
+                Key::Backspace => {
+                    prompt.text.pop();
+                    self.prompt = Some(prompt);
+                },
+                Key::Char(ch) => {
+                    prompt.text.push(ch);
+                    self.prompt = Some(prompt);
+                },
+                _ => self.prompt = Some(prompt),
+            }
+            return true;
+        }
+
+        match key {
+            Key::Char('q') | Key::Esc | Key::Ctrl('c') => return false,
+            Key::Char('s') => self.prompt = Some(Prompt { action: Action::Start, text: String::new() }),
+            Key::Char('t') => self.stop(),
+            Key::Char('r') => self.restart(),
+            Key::Char('n') => {
+                if let Some(record) = self.processor.last_record() {
+                    self.prompt = Some(Prompt { action: Action::EditNote, text: record.note });
+                }
+            },
+            Key::Char('c') => self.root_items_only = !self.root_items_only,
+            Key::Left | Key::Char('h') => self.date = self.date.pred(),
+            Key::Right | Key::Char('l') => self.date = self.date.succ(),
+            Key::Home | Key::Char('.') => self.date = Local::now().date(),
+            _ => (),
+        }
+        true
+    }
+
+    fn submit(&mut self, prompt: Prompt) {
+        let note = prompt.text.trim().to_string();
+        match prompt.action {
+            Action::Start => {
+                if self.processor.is_running() {
+                    self.processor.stop_record(-1, None);
+                }
+                self.processor.start_record(Some(note.clone()));
+                self.message = format!("Started: {}", note);
+            },
+            Action::EditNote => {
+                self.processor.update_note(-1, note.clone());
+                self.message = format!("Note changed: {}", note);
+            },
+        }
+    }
+
+    fn stop(&mut self) {
+        if self.processor.is_running() {
+            self.processor.stop_record(-1, None);
+            self.message = "Stopped".to_string();
+        } else {
+            self.message = "Nothing is running".to_string();
+        }
+    }
+
+    fn restart(&mut self) {
+        if self.processor.last_record().is_none() {
+            self.message = "Nothing to restart".to_string();
+        } else if self.processor.is_running() {
+            self.message = "Already running".to_string();
+        } else {
+            self.processor.restart_record(-1, None);
+            self.message = "Restarted".to_string();
+        }
+    }
+
+    fn draw<W: Write>(&self, screen: &mut W) -> io::Result<()> {
+        let (width, height) = termion::terminal_size().unwrap_or((80, 24));
+        let height = height.max(3);
+        let mut lines = vec![
+            format!("{}TimeTracker{}  {}", style::Bold, style::Reset, self.date.format("%a %Y-%m-%d")),
+            self.running_line(),
+            String::new(),
+        ];
+
+        lines.push(format!("{}Records{}", style::Bold, style::Reset));
+        lines.extend(self.processor.records_on(self.date).iter().map(|record| record.to_string()));
+        lines.push(String::new());
+
+        let (nodes, total) = self.processor.report_nodes(Some(self.date), Some(self.date));
+        let collapsed = if self.root_items_only { " (collapsed)" } else { "" };
+        lines.push(format!("{}Report{}{}", style::Bold, style::Reset, collapsed));
+        for node in nodes.iter() {
+            lines.extend(node.to_string(false, self.root_items_only).lines().map(|line| line.to_string()));
+        }
+        lines.push(format!("Total: {}", total));
+
+        let footer = match self.prompt {
+            Some(Prompt { action: Action::Start, ref text }) => format!("Start note: {}_", text),
+            Some(Prompt { action: Action::EditNote, ref text }) => format!("Edit note: {}_", text),
+            None => self.message.clone(),
+        };
+        let body_height = (height as usize).saturating_sub(3);
+        lines.truncate(body_height);
+
+        write!(screen, "{}", clear::All)?;
+        for (row, line) in lines.iter().enumerate() {
+            write!(screen, "{}{}", cursor::Goto(1, row as u16 + 1), Self::fit(line, width))?;
+        }
+        write!(screen, "{}{}", cursor::Goto(1, height - 1), Self::fit(HELP, width))?;
+        write!(screen, "{}{}", cursor::Goto(1, height), Self::fit(&footer, width))?;
+        screen.flush()
+    }
+
+    fn running_line(&self) -> String {
+        match self.processor.last_record() {
+            Some(ref record) if record.activity.is_none() && record.start.is_some() => {
+                let start = record.start.expect("Running record should have start");
+                let elapsed = Local::now().signed_duration_since(start) - record.rest.unwrap_or_else(Duration::zero);
+                let seconds = elapsed.num_seconds().max(0);
+                format!(
+                    "Running: {}  {:02}:{:02}:{:02}",
+                    record.note, seconds / 3600, seconds / 60 % 60, seconds % 60
+                )
+            },
+            _ => "Nothing is running".to_string(),
+        }
+    }
+
+    /// Cuts the line to the terminal width counting only the visible characters, the escape sequences of styles
+    /// take no width and are kept whole
+    fn fit(line: &str, width: u16) -> String {
+        let mut fitted = String::new();
+        let mut visible = 0;
+        let mut chars = line.chars();
+        while let Some(ch) = chars.next() {
+            if ch == '\x1b' {
+                fitted.push(ch);
+                if let Some(next) = chars.next() {
+                    fitted.push(next);
+                    if next == '[' {
+                        for ch in chars.by_ref() {
+                            fitted.push(ch);
+                            if ('@'..='~').contains(&ch) {
+                                break;
+                            }
+                        }
+                    }
+                }
+            } else if visible < width as usize {
+                fitted.push(ch);
+                visible += 1;
+            }
+        }
+        fitted
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fit_styled_line() {
+        let line = format!("{}Records{} of the day", style::Bold, style::Reset);
+        assert_eq!(format!("{}Records{} of", style::Bold, style::Reset), Tui::fit(&line, 10));
+        assert_eq!(format!("{}Rec{}", style::Bold, style::Reset), Tui::fit(&line, 3));
+        assert_eq!("Running", Tui::fit("Running: task", 7));
+    }
+}
@@ -0,0 +1,109 @@
+/// Zip archive of uncompressed files, enough for the OpenDocument and Office Open XML containers
+pub struct ZipWriter {
+    data: Vec<u8>,
+    directory: Vec<u8>,
+    entries: u16,
+}
+
+impl ZipWriter {
+    const VERSION: u16 = 20;
+    /// 1980-01-01 in the MS-DOS date format
+    const DOS_DATE: u16 = 0x21;
+
+    pub fn new() -> Self {
+        ZipWriter { data: Vec::new(), directory: Vec::new(), entries: 0 }
+    }
+
+    /// Adds the file stored without compression
+    pub fn add(&mut self, name: &str, content: &[u8]) {
+        let offset = self.data.len() as u32;
+        let crc = crc32(content);
+
+        put_u32(&mut self.data, 0x0403_4b50);
+        put_u16(&mut self.data, Self::VERSION);
+        self.put_entry_fields(name, content, crc, true);
+        self.data.extend_from_slice(name.as_bytes());
+        self.data.extend_from_slice(content);
+
+        put_u32(&mut self.directory, 0x0201_4b50);
+        put_u16(&mut self.directory, Self::VERSION);
+        put_u16(&mut self.directory, Self::VERSION);
+        self.put_entry_fields(name, content, crc, false);
+        put_u16(&mut self.directory, 0);
+        put_u16(&mut self.directory, 0);
+        put_u16(&mut self.directory, 0);
+        put_u32(&mut self.directory, 0);
+        put_u32(&mut self.directory, offset);
+        self.directory.extend_from_slice(name.as_bytes());
+
+        self.entries += 1;
+    }
+
+    /// Flags, method, time, date, CRC, sizes, name and extra field lengths shared by both headers
+    fn put_entry_fields(&mut self, name: &str, content: &[u8], crc: u32, local: bool) {
+        let out = if local { &mut self.data } else { &mut self.directory };
+        put_u16(out, 0);
+        put_u16(out, 0);
+        put_u16(out, 0);
+        put_u16(out, Self::DOS_DATE);
+        put_u32(out, crc);
+        put_u32(out, content.len() as u32);
+        put_u32(out, content.len() as u32);
+        put_u16(out, name.len() as u16);
+        put_u16(out, 0);
+    }
+
+    pub fn finish(mut self) -> Vec<u8> {
+        let offset = self.data.len() as u32;
+        let size = self.directory.len() as u32;
+        self.data.append(&mut self.directory);
+
+        put_u32(&mut self.data, 0x0605_4b50);
+        put_u16(&mut self.data, 0);
+        put_u16(&mut self.data, 0);
+        put_u16(&mut self.data, self.entries);
+        put_u16(&mut self.data, self.entries);
+        put_u32(&mut self.data, size);
+        put_u32(&mut self.data, offset);
+        put_u16(&mut self.data, 0);
+        self.data
+    }
+}
+
+fn put_u16(out: &mut Vec<u8>, value: u16) {
+    out.extend_from_slice(&[value as u8, (value >> 8) as u8]);
+}
+
+fn put_u32(out: &mut Vec<u8>, value: u32) {
+    put_u16(out, value as u16);
+    put_u16(out, (value >> 16) as u16);
+}
+
+/// CRC-32 of the zip format (IEEE polynomial)
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB8_8320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zip_stored_files() {
+        assert_eq!(0xCBF4_3926, crc32(b"123456789"));
+
+        let mut zip = ZipWriter::new();
+        zip.add("mimetype", b"text/plain");
+        let data = zip.finish();
+        assert_eq!(&[0x50, 0x4b, 0x03, 0x04], &data[..4]);
+        assert_eq!(b"text/plain", &data[38..48]);
+        assert_eq!(30 + 8 + 10 + 46 + 8 + 22, data.len());
+    }
+}
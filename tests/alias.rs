@@ -0,0 +1,30 @@
+#[macro_use]
+mod common;
+
+use file_assertions::{create_file, assert_content};
+use crate::common::TestPaths;
+
+#[test]
+fn set_note_alias() {
+    let test_paths = TestPaths::new("test_alias");
+    test_paths.init();
+    let (journal_file, config_file, _) = test_paths.paths();
+
+    let config_content = format!(
+        "journal_file = {:?}\n[aliases]\nstandup = \"Meetings daily standup\"\nreview = \"Day work review PR-{{1}}\"\n",
+        journal_file.as_os_str()
+    );
+    create_file!(config_file, config_content);
+
+    let content = "\
+        [2018-12-03 09:00:00, 15] record1\n\
+        [2018-12-03 09:15:00, 60] record2\n";
+    create_file!(journal_file, content);
+
+    run!("tt-cli set -n 1 note standup");
+    run!("tt-cli set note review 42 fixes");
+    let expected = "\
+        [2018-12-03 09:00:00, 15] Meetings daily standup\n\
+        [2018-12-03 09:15:00, 60] Day work review PR-42 fixes\n";
+    assert_content!(journal_file, expected);
+}
@@ -140,6 +140,7 @@ impl TestPaths {
         (&self.journal_file, &self.config_file, &self.test_dir)
     }
 
+    #[allow(dead_code)]
     pub fn journal_file(&self) -> &PathBuf {
         &self.journal_file
     }
@@ -0,0 +1,82 @@
+#[macro_use]
+mod common;
+
+use std::env;
+use file_assertions::{create_file, assert_content};
+use crate::common::TestPaths;
+
+#[test]
+fn edit_records() {
+    let test_paths = TestPaths::new("test_edit");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+
+    let content = "\
+        [2018-12-02 18:00:00, 60] Day work task1\n\
+        [2018-12-03 09:00:00, 60] Day work task1\n\
+        [2018-12-03 10:00:00, 30] Some work\n\
+        [2018-12-04 09:00:00, ] Other work\n";
+    create_file!(journal_file, content);
+
+    env::remove_var("VISUAL");
+    env::set_var("EDITOR", "sed -i -e s/task1/task2/ -e s/30/45/");
+    run!("tt-cli edit --date 2018-12-03");
+    let expected = "\
+        [2018-12-02 18:00:00, 60] Day work task1\n\
+        [2018-12-03 09:00:00, 60] Day work task2\n\
+        [2018-12-03 10:00:00, 45] Some work\n\
+        [2018-12-04 09:00:00, ] Other work\n";
+    assert_content!(journal_file, expected);
+
+    env::set_var("EDITOR", "sed -i s/Some/\\\\nbroken\\\\n/");
+    run!("tt-cli edit --date 2018-12-03");
+    assert_content!(journal_file, expected);
+
+    env::set_var("EDITOR", "sed -i /Some/d");
+    run!("tt-cli edit -n 0..3");
+    let expected = "\
+        [2018-12-02 18:00:00, 60] Day work task1\n\
+        [2018-12-03 09:00:00, 60] Day work task2\n\
+        [2018-12-04 09:00:00, ] Other work\n";
+    assert_content!(journal_file, expected);
+}
+
+#[test]
+fn edit_matched_records() {
+    let test_paths = TestPaths::new("test_edit_matched");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+
+    let content = "\
+        [2018-12-01 09:00:00, 60] Day work task1\n\
+        [2018-12-01 10:00:00, 30] Some work\n\
+        # moved from the old journal\n\
+        [2018-12-02 09:00:00, 60] Day work task2\n\
+        [2018-12-02 10:00:00, 30] Other work\n";
+    create_file!(journal_file, content);
+
+    env::remove_var("VISUAL");
+    env::set_var("EDITOR", "sed -i s/Day/Client/");
+    run!("tt-cli edit --match \"^Day\" --all-matches");
+    let expected = "\
+        [2018-12-01 09:00:00, 60] Client work task1\n\
+        [2018-12-01 10:00:00, 30] Some work\n\
+        # moved from the old journal\n\
+        [2018-12-02 09:00:00, 60] Client work task2\n\
+        [2018-12-02 10:00:00, 30] Other work\n";
+    assert_content!(journal_file, expected);
+
+    env::set_var("EDITOR", "sed -i 's/02 09:00/01 10:00/'");
+    run!("tt-cli edit --match task2");
+    assert_content!(journal_file, expected);
+
+    env::set_var("EDITOR", "sed -i 's/01 10:00/02 11:00/'");
+    run!("tt-cli edit --match Some");
+    let expected = "\
+        [2018-12-01 09:00:00, 60] Client work task1\n\
+        # moved from the old journal\n\
+        [2018-12-02 09:00:00, 60] Client work task2\n\
+        [2018-12-02 10:00:00, 30] Other work\n\
+        [2018-12-02 11:00:00, 30] Some work\n";
+    assert_content!(journal_file, expected);
+}
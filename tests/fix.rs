@@ -0,0 +1,62 @@
+#[macro_use]
+mod common;
+
+use file_assertions::{create_file, assert_content};
+use crate::common::TestPaths;
+
+#[test]
+fn fix_journal() {
+    let test_paths = TestPaths::new("test_fix");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+
+    let content = "\
+        [2018-12-03 10:00:00, 30] record2\n\
+        [2018-12-03 09:00:00, ] record1\n\
+        [2018-12-03 10:20:00, 30 (10)] record3\n\
+        [2018-12-03 11:30:00, ] record4\n";
+    create_file!(journal_file, content);
+
+    run!("tt-cli fix --dry-run");
+    assert_content!(journal_file, content);
+
+    run!("tt-cli fix");
+    let expected = "\
+        [2018-12-03 09:00:00, 60] record1\n\
+        [2018-12-03 10:00:00, 20] record2\n\
+        [2018-12-03 10:20:00, 30 (10)] record3\n\
+        [2018-12-03 11:30:00, ] record4\n";
+    assert_content!(journal_file, expected);
+
+    let content = "\
+        [2018-12-03 09:00:00, ] record1\n\
+        [2018-12-03 12:00:00, ] record2\n";
+    create_file!(journal_file, content);
+
+    run!("tt-cli fix --cap 45");
+    let expected = "\
+        [2018-12-03 09:00:00, 45] record1\n\
+        [2018-12-03 12:00:00, ] record2\n";
+    assert_content!(journal_file, expected);
+}
+
+#[test]
+fn fix_journal_over_midnight() {
+    let test_paths = TestPaths::new("test_fix_midnight");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+
+    let content = "\
+        [2018-12-03 09:00:00, ] record1\n\
+        [2018-12-04 09:00:00, 60] record2\n";
+    create_file!(journal_file, content);
+
+    run!("tt-cli fix");
+    assert_content!(journal_file, content);
+
+    run!("tt-cli fix --cap 480");
+    let expected = "\
+        [2018-12-03 09:00:00, 480] record1\n\
+        [2018-12-04 09:00:00, 60] record2\n";
+    assert_content!(journal_file, expected);
+}
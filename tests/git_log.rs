@@ -0,0 +1,46 @@
+#[macro_use]
+mod common;
+
+use std::process::Command;
+use file_assertions::{create_file, assert_content};
+use crate::common::TestPaths;
+
+fn git(repo: &str, args: &[&str], date: &str) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(repo)
+        .arg("-c")
+        .arg("user.name=Tester")
+        .arg("-c")
+        .arg("user.email=tester@example.com")
+        .args(args)
+        .env("GIT_AUTHOR_DATE", date)
+        .env("GIT_COMMITTER_DATE", date)
+        .status()
+        .expect("Failed execute git");
+    assert!(status.success(), "git {:?} failed", args);
+}
+
+#[test]
+fn append_commit_summaries() {
+    let test_paths = TestPaths::new("test_git_log");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+    let repo = "target/test_git_log";
+
+    let content = "\
+        [2018-12-03 09:00:00, 60] Day work task1\n\
+        [2018-12-03 11:00:00, 30] Day work task2\n";
+    create_file!(journal_file, content);
+
+    git(repo, &["init", "-q"], "2018-12-03T09:00:00");
+    git(repo, &["commit", "-q", "--allow-empty", "-m", "Fix login form"], "2018-12-03T09:30:00");
+    git(repo, &["commit", "-q", "--allow-empty", "-m", "Lunch fixes"], "2018-12-03T12:30:00");
+    git(repo, &["commit", "-q", "--allow-empty", "-m", "Add menu"], "2018-12-04T09:30:00");
+
+    run!("tt-cli git-log --repo target/test_git_log --date 2018-12-03 --append --yes");
+    let expected = "\
+        [2018-12-03 09:00:00, 60] Day work task1 Fix login form\n\
+        [2018-12-03 11:00:00, 30] Day work task2\n";
+    assert_content!(journal_file, expected);
+}
@@ -0,0 +1,38 @@
+#[macro_use]
+mod common;
+
+use std::fs;
+use std::process::Command;
+use file_assertions::create_file;
+use tt_core::journal::Journal;
+use crate::common::TestPaths;
+
+#[test]
+fn checkout_branch() {
+    let test_paths = TestPaths::new("test_hook");
+    let journal = test_paths.init();
+    let journal_file = test_paths.journal_file();
+    let (_, config_file, _) = test_paths.paths();
+
+    let config_content = format!(
+        "journal_file = {:?}\nbranch_note_template = \"Project {{ticket}} {{title}}\"\n",
+        journal_file.as_os_str()
+    );
+    create_file!(config_file, config_content);
+
+    let status = Command::new("git").arg("init").arg("-q").arg("target/test_hook").status().expect("Failed execute git");
+    assert!(status.success());
+    run!("tt-cli hook install --repo target/test_hook");
+    let hook = fs::read_to_string("target/test_hook/.git/hooks/post-checkout").expect("The hook is not installed");
+    assert!(hook.contains("tt hook checkout"));
+
+    run!("tt-cli hook checkout feature/ABC-123-login");
+    run!("tt-cli hook checkout feature/ABC-123-login");
+    run!("tt-cli hook checkout bugfix/fix-menu");
+
+    let notes = (0..3)
+        .filter_map(|offset| journal.get(&[], Some(offset)).expect("Can't get record"))
+        .map(|record| (record.note, record.activity.is_some()))
+        .collect::<Vec<_>>();
+    assert_eq!(vec![("Project ABC-123 login".to_string(), true), ("Project fix menu".to_string(), false)], notes);
+}
@@ -0,0 +1,85 @@
+#[macro_use]
+mod common;
+
+use file_assertions::{create_file, assert_content};
+use crate::common::TestPaths;
+
+#[test]
+fn import_csv() {
+    let test_paths = TestPaths::new("test_import");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+    let import_file = "target/test_import/import.csv";
+
+    let content = "\
+        [2018-12-03 09:00:00, 60] Day work task1\n\
+        [2018-12-03 11:00:00, 30] Day work task2\n";
+    create_file!(journal_file, content);
+
+    let import_content = "\
+        start,end,note\n\
+        2018-12-03 11:00,2018-12-03 11:30,Day work task2\n\
+        2018-12-03 10:00,2018-12-03 10:45,Meetings standup\n\
+        2018-12-03 12:00,2018-12-03 12:20,Lunch\n";
+    create_file!(import_file, import_content);
+
+    run!("tt-cli import --from csv target/test_import/import.csv");
+    let expected = "\
+        [2018-12-03 09:00:00, 60] Day work task1\n\
+        [2018-12-03 10:00:00, 45] Meetings standup\n\
+        [2018-12-03 11:00:00, 30] Day work task2\n\
+        [2018-12-03 12:00:00, 20] Lunch\n";
+    assert_content!(journal_file, expected);
+
+    run!("tt-cli import --from csv target/test_import/import.csv");
+    assert_content!(journal_file, expected);
+}
+
+#[test]
+fn import_open_records() {
+    let test_paths = TestPaths::new("test_import_open");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+    let import_file = "target/test_import_open/import.csv";
+
+    let content = "[2018-12-03 09:00:00, 60] Day work task1\n";
+    create_file!(journal_file, content);
+
+    let import_content = "\
+        start,end,note\n\
+        2018-12-03 10:00,,Meetings standup\n\
+        2018-12-03 13:00,,Day work task2\n\
+        2018-12-03 12:00,2018-12-03 12:20,Lunch\n";
+    create_file!(import_file, import_content);
+
+    run!("tt-cli import --from csv target/test_import_open/import.csv");
+    let expected = "\
+        [2018-12-03 09:00:00, 60] Day work task1\n\
+        [2018-12-03 12:00:00, 20] Lunch\n\
+        [2018-12-03 13:00:00, ] Day work task2\n";
+    assert_content!(journal_file, expected);
+}
+
+#[test]
+fn import_invalid_rows() {
+    let test_paths = TestPaths::new("test_import_invalid");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+    let import_file = "target/test_import_invalid/import.csv";
+
+    let content = "[2018-12-03 09:00:00, 60] Day work task1\n";
+    create_file!(journal_file, content);
+
+    let import_content = "\
+        start,end,note\n\
+        2018-12-03 10:00,2018-12-03 10:45,\"Meetings standup\r\nand planning\"\n\
+        2018-12-03 12:00,2018-12-03 11:40,Lunch\n\
+        2018-12-03 13:00,2018-12-03 13:00,Day work task2\n";
+    create_file!(import_file, import_content);
+
+    run!("tt-cli import --from csv target/test_import_invalid/import.csv");
+    let expected = "\
+        [2018-12-03 09:00:00, 60] Day work task1\n\
+        [2018-12-03 10:00:00, 45] Meetings standup and planning\n";
+    assert_content!(journal_file, expected);
+}
@@ -0,0 +1,52 @@
+#[macro_use]
+mod common;
+
+use file_assertions::{create_file, assert_content};
+use crate::common::TestPaths;
+
+#[test]
+fn import_calendar_events() {
+    let test_paths = TestPaths::new("test_import_calendar");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+    let (_, config_file, _) = test_paths.paths();
+    let calendar_file = "target/test_import_calendar/calendar.ics";
+
+    let config_content = format!(
+        "journal_file = {:?}\n[calendar_notes]\nstandup = \"Meetings\"\n",
+        journal_file.as_os_str()
+    );
+    create_file!(config_file, config_content);
+
+    let content = "\
+        [2018-12-03 09:00:00, 60] Day work task1\n\
+        [2018-12-03 11:00:00, 30] Day work task2\n";
+    create_file!(journal_file, content);
+
+    let calendar_content = "\
+        BEGIN:VCALENDAR\r\n\
+        BEGIN:VEVENT\r\n\
+        DTSTART:20181203T100000\r\n\
+        DTEND:20181203T101500\r\n\
+        SUMMARY:Daily standup\r\n\
+        END:VEVENT\r\n\
+        BEGIN:VEVENT\r\n\
+        DTSTART:20181203T110000\r\n\
+        DTEND:20181203T120000\r\n\
+        SUMMARY:Sprint planning\r\n\
+        END:VEVENT\r\n\
+        BEGIN:VEVENT\r\n\
+        DTSTART:20181204T100000\r\n\
+        DTEND:20181204T101500\r\n\
+        SUMMARY:Daily standup\r\n\
+        END:VEVENT\r\n\
+        END:VCALENDAR\r\n";
+    create_file!(calendar_file, calendar_content);
+
+    run!("tt-cli import-calendar --date 2018-12-03 --yes target/test_import_calendar/calendar.ics");
+    let expected = "\
+        [2018-12-03 09:00:00, 60] Day work task1\n\
+        [2018-12-03 10:00:00, 15] Meetings Daily standup\n\
+        [2018-12-03 11:00:00, 30] Day work task2\n";
+    assert_content!(journal_file, expected);
+}
@@ -0,0 +1,89 @@
+#[macro_use]
+mod common;
+
+use std::fs;
+use std::time::Instant;
+use file_assertions::create_file;
+use tt_core::record::{Duration, Local, Record, TimeZone};
+use crate::common::TestPaths;
+
+#[test]
+fn list_date_with_index() {
+    let test_paths = TestPaths::new("test_index");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+
+    let content = "\
+        [2018-12-01 09:00:00, 60] Day work task1\n\
+        [2018-12-02 09:00:00, 60] Day work task2\n\
+        # moved\n\
+        [2018-12-01 12:00:00, 30] Lunch\n\
+        [2018-12-03 09:00:00, 60] Day work task3\n";
+    create_file!(journal_file, content);
+
+    let expected = "\
+        [2018-12-03 09:00:00, 60] Day work task3\n";
+    assert_output!("tt-cli list 2018-12-02" => expected);
+    assert!(fs::metadata("target/test_index/journal.txt.idx").is_ok());
+    assert_output!("tt-cli list 2018-12-02" => expected);
+
+    let content = format!("{}[2018-12-03 11:00:00, 45] Day work task4\n", content);
+    create_file!(journal_file, &content);
+    let expected = "\
+        [2018-12-03 09:00:00, 60] Day work task3\n\
+        [2018-12-03 11:00:00, 45] Day work task4\n";
+    assert_output!("tt-cli list 2018-12-02" => expected);
+    assert_output!("tt-cli report 2018-12-03" => "105  Day work\n  60  task3\n  45  task4\n----------\nTotal: 105");
+
+    run!("tt-cli start Day work task5");
+    run!("tt-cli stop");
+    let index = fs::read_to_string("target/test_index/journal.txt.idx").expect("Can't read index");
+    let size = fs::metadata(journal_file).expect("Can't read journal metadata").len();
+    assert!(index.starts_with(&format!("{}\t", size)), "{}", index);
+}
+
+/// Compares the report of the last day without the index, read whole, and with the index,
+/// run with `cargo test --release --test index -- --ignored --nocapture`
+#[test]
+#[ignore]
+fn benchmark_date_bounded_queries() {
+    let test_paths = TestPaths::new("test_index_benchmark");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+    let index_file = "target/test_index_benchmark/journal.txt.idx";
+
+    let mut content = String::new();
+    let mut day = Local.ymd(2014, 1, 1);
+    for _ in 0..5 * 365 {
+        for hour in 9..17 {
+            let mut record = Record::now();
+            record.start = Some(day.and_hms(hour, 0, 0));
+            record.activity = Some(Duration::minutes(50));
+            record.rest = Some(Duration::minutes(10));
+            record.note = format!("Project{} task{}", hour % 3, hour);
+            content.push_str(&format!("{}\n", record.to_string()));
+        }
+        day = day.succ();
+    }
+    create_file!(journal_file, &content);
+    let last_day = &format!("tt-cli report {}", day.pred().format("%Y-%m-%d"));
+
+    let measure = |remove_index: bool| {
+        (0..5)
+            .map(|_| {
+                if remove_index {
+                    fs::remove_file(index_file).ok();
+                }
+                let started = Instant::now();
+                run!(last_day);
+                started.elapsed()
+            })
+            .min()
+            .expect("Can't measure the report")
+    };
+    let without_index = measure(true);
+    let with_index = measure(false);
+    println!("Journal of {} lines", content.lines().count());
+    println!("Report of the last day without the index: {:?}", without_index);
+    println!("Report of the last day with the index: {:?}", with_index);
+}
@@ -0,0 +1,26 @@
+#[macro_use]
+mod common;
+
+use file_assertions::create_file;
+use crate::common::TestPaths;
+
+#[test]
+fn complete_notes() {
+    let test_paths = TestPaths::new("test_notes");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+
+    let content = "\
+        [2018-12-03 09:00:00, 60] Day work task2\n\
+        [2018-12-03 10:00:00, 30] Day work task1\n\
+        [2018-12-03 10:30:00, 45] Day work task2\n\
+        [2018-12-03 11:15:00, 15] Lunch\n";
+    create_file!(journal_file, content);
+
+    assert_output!(
+        "tt-cli notes" => "Day\nLunch",
+        "tt-cli notes Day work" => "task2\ntask1",
+        "tt-cli notes Day work task2" => "",
+        "tt-cli notes Other" => ""
+    );
+}
@@ -0,0 +1,26 @@
+#[macro_use]
+mod common;
+
+use tt_core::journal::Journal;
+use crate::common::TestPaths;
+
+#[test]
+fn pomodoro_records() {
+    let test_paths = TestPaths::new("test_pomodoro");
+    let journal = test_paths.init();
+    let journal_file = test_paths.journal_file();
+
+    run!("tt-cli pomodoro --work 1s --break 0 --cycles 2 Day work task1");
+
+    for offset in 0..2 {
+        let record = journal.get(&[], Some(offset))
+            .expect(&format!("Can't get record from {:?}", journal_file))
+            .expect(&format!("The record in {:?} is empty", journal_file));
+        assert_eq!("Day work task1", record.note);
+        assert!(record.start.is_some());
+        assert!(record.activity.is_some());
+    }
+    let record = journal.get(&[], Some(2))
+        .expect(&format!("Can't get record from {:?}", journal_file));
+    assert!(record.is_none());
+}
@@ -0,0 +1,70 @@
+#[macro_use]
+mod common;
+
+use file_assertions::create_file;
+use tt_core::journal::Journal;
+use crate::common::TestPaths;
+
+#[test]
+fn resume_record() {
+    let test_paths = TestPaths::new("test_resume");
+    let journal = test_paths.init();
+    let journal_file = test_paths.journal_file();
+
+    let content = "\
+        [2018-12-03 09:00:00, 60] Day work task1\n\
+        [2018-12-03 10:00:00, 30] Lunch\n\
+        [2018-12-03 10:30:00, 45] Day work task2\n";
+    create_file!(journal_file, content);
+
+    run!("tt-cli resume TASK1");
+
+    let record = journal.get(&[], Some(-1))
+        .expect(&format!("Can't get record from {:?}", journal_file))
+        .expect(&format!("The record in {:?} is empty", journal_file));
+    assert_eq!("Day work task1", record.note);
+    assert!(record.start.is_some());
+    assert!(record.activity.is_none());
+
+    run!("tt-cli resume lunch");
+
+    let record = journal.get(&[], Some(3))
+        .expect(&format!("Can't get record from {:?}", journal_file))
+        .expect(&format!("The record in {:?} is empty", journal_file));
+    assert_eq!("Day work task1", record.note);
+    assert!(record.activity.is_some());
+    let record = journal.get(&[], Some(-1))
+        .expect(&format!("Can't get record from {:?}", journal_file))
+        .expect(&format!("The record in {:?} is empty", journal_file));
+    assert_eq!("Lunch", record.note);
+
+    run!("tt-cli resume unknown");
+    run!("tt-cli resume");
+
+    let record = journal.get(&[], Some(5))
+        .expect(&format!("Can't get record from {:?}", journal_file));
+    assert!(record.is_none());
+}
+
+#[test]
+fn resume_plain_text_query() {
+    let test_paths = TestPaths::new("test_resume_plain");
+    let journal = test_paths.init();
+    let journal_file = test_paths.journal_file();
+
+    let content = "\
+        [2018-12-03 09:00:00, 60] Build C++ module\n\
+        [2018-12-03 10:00:00, 30] Lunch\n";
+    create_file!(journal_file, content);
+
+    run!("tt-cli resume \"fix (login\"");
+    run!("tt-cli resume c++");
+
+    let record = journal.get(&[], Some(-1))
+        .expect(&format!("Can't get record from {:?}", journal_file))
+        .expect(&format!("The record in {:?} is empty", journal_file));
+    assert_eq!("Build C++ module", record.note);
+    let record = journal.get(&[], Some(3))
+        .expect(&format!("Can't get record from {:?}", journal_file));
+    assert!(record.is_none());
+}
@@ -0,0 +1,200 @@
+#[macro_use]
+mod common;
+
+use file_assertions::{create_file, assert_content};
+use crate::common::TestPaths;
+
+#[test]
+fn select_record() {
+    let test_paths = TestPaths::new("test_select");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+
+    let content = "\
+        [2018-12-03 09:00:00, 60] Day work task1\n\
+        [2018-12-03 10:00:00, 25] Some work\n\
+        [2018-12-03 10:30:00, 45] Day work task2\n\
+        [2018-12-03 11:15:00, ] Other work\n";
+    create_file!(journal_file, content);
+
+    run!("tt-cli set --match task1 note Day work task3");
+    let expected = "\
+        [2018-12-03 09:00:00, 60] Day work task3\n\
+        [2018-12-03 10:00:00, 25] Some work\n\
+        [2018-12-03 10:30:00, 45] Day work task2\n\
+        [2018-12-03 11:15:00, ] Other work\n";
+    assert_content!(journal_file, expected);
+
+    run!("tt-cli set --match \"^Day work\" act 40");
+    let expected = "\
+        [2018-12-03 09:00:00, 60] Day work task3\n\
+        [2018-12-03 10:00:00, 25] Some work\n\
+        [2018-12-03 10:30:00, 40] Day work task2\n\
+        [2018-12-03 11:15:00, ] Other work\n";
+    assert_content!(journal_file, expected);
+
+    run!("tt-cli set --at \"2018-12-03 10:10\" rest 5");
+    let expected = "\
+        [2018-12-03 09:00:00, 60] Day work task3\n\
+        [2018-12-03 10:00:00, 25 (5)] Some work\n\
+        [2018-12-03 10:30:00, 40] Day work task2\n\
+        [2018-12-03 11:15:00, ] Other work\n";
+    assert_content!(journal_file, expected);
+
+    run!("tt-cli del --id 20181203090000");
+    let expected = "\
+        [2018-12-03 10:00:00, 25 (5)] Some work\n\
+        [2018-12-03 10:30:00, 40] Day work task2\n\
+        [2018-12-03 11:15:00, ] Other work\n";
+    assert_content!(journal_file, expected);
+
+    run!("tt-cli del --match \"Day work\"");
+    let expected = "\
+        [2018-12-03 10:00:00, 25 (5)] Some work\n\
+        [2018-12-03 11:15:00, ] Other work\n";
+    assert_content!(journal_file, expected);
+}
+
+#[test]
+fn select_range() {
+    let test_paths = TestPaths::new("test_select_range");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+
+    let content = "\
+        [2018-12-02 18:00:00, 60] Day work task1\n\
+        [2018-12-03 09:00:00, 60] Day work task1\n\
+        [2018-12-03 10:00:00, 30] Some work\n\
+        [2018-12-03 10:30:00, 45] Day work task2\n\
+        [2018-12-04 09:00:00, ] Other work\n";
+    create_file!(journal_file, content);
+
+    run!("tt-cli set note --match \"^Day work\" --all-matches --replace \"Client work\"");
+    assert_content!(journal_file, content);
+
+    run!("tt-cli set note --match \"^Day work\" --all-matches --replace \"Client work\" --yes");
+    let expected = "\
+        [2018-12-02 18:00:00, 60] Client work task1\n\
+        [2018-12-03 09:00:00, 60] Client work task1\n\
+        [2018-12-03 10:00:00, 30] Some work\n\
+        [2018-12-03 10:30:00, 45] Client work task2\n\
+        [2018-12-04 09:00:00, ] Other work\n";
+    assert_content!(journal_file, expected);
+
+    run!("tt-cli set --date 2018-12-03 --yes time +1h");
+    let expected = "\
+        [2018-12-02 18:00:00, 60] Client work task1\n\
+        [2018-12-03 10:00:00, 60] Client work task1\n\
+        [2018-12-03 11:00:00, 30] Some work\n\
+        [2018-12-03 11:30:00, 45] Client work task2\n\
+        [2018-12-04 09:00:00, ] Other work\n";
+    assert_content!(journal_file, expected);
+
+    run!("tt-cli set -n 1..3 -y rest 5");
+    assert_content!(journal_file, expected);
+
+    run!("tt-cli set -n 1..3 -y act -5m");
+    let expected = "\
+        [2018-12-02 18:00:00, 60] Client work task1\n\
+        [2018-12-03 10:00:00, 60] Client work task1\n\
+        [2018-12-03 11:00:00, 25] Some work\n\
+        [2018-12-03 11:30:00, 40] Client work task2\n\
+        [2018-12-04 09:00:00, ] Other work\n";
+    assert_content!(journal_file, expected);
+
+    run!("tt-cli del --date 2018-12-03 --yes");
+    let expected = "\
+        [2018-12-02 18:00:00, 60] Client work task1\n\
+        [2018-12-04 09:00:00, ] Other work\n";
+    assert_content!(journal_file, expected);
+}
+
+#[test]
+fn select_all_matches() {
+    let test_paths = TestPaths::new("test_select_all_matches");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+
+    let content = "\
+        [2018-12-02 18:00:00, 60] Day work task1\n\
+        [2018-12-03 09:00:00, 60] Day work task1\n\
+        [2018-12-03 10:00:00, 30] Some work\n\
+        [2018-12-03 10:30:00, 45] Day work task2\n";
+    create_file!(journal_file, content);
+
+    run!("tt-cli set note --match task1 --replace task3");
+    let expected = "\
+        [2018-12-02 18:00:00, 60] Day work task1\n\
+        [2018-12-03 09:00:00, 60] Day work task3\n\
+        [2018-12-03 10:00:00, 30] Some work\n\
+        [2018-12-03 10:30:00, 45] Day work task2\n";
+    assert_content!(journal_file, expected);
+
+    run!("tt-cli set --match \"^Day work\" --all-matches --yes act -10m");
+    let expected = "\
+        [2018-12-02 18:00:00, 50] Day work task1\n\
+        [2018-12-03 09:00:00, 50] Day work task3\n\
+        [2018-12-03 10:00:00, 30] Some work\n\
+        [2018-12-03 10:30:00, 35] Day work task2\n";
+    assert_content!(journal_file, expected);
+
+    run!("tt-cli del --match \"^Day work\" --all-matches --yes");
+    let expected = "\
+        [2018-12-03 10:00:00, 30] Some work\n";
+    assert_content!(journal_file, expected);
+}
+
+#[test]
+fn select_range_overlapping() {
+    let test_paths = TestPaths::new("test_select_range_overlapping");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+
+    let content = "\
+        [2018-12-03 09:00:00, 60] Day work task1\n\
+        [2018-12-03 10:00:00, 30] Day work task2\n\
+        [2018-12-03 11:00:00, 60] Some work\n\
+        [2018-12-04 09:00:00, 60] Other work\n";
+    create_file!(journal_file, content);
+
+    run!("tt-cli set --match \"^Day work\" --all-matches --yes time +1h");
+    assert_content!(journal_file, content);
+
+    run!("tt-cli set --date 2018-12-03 --yes time +1h");
+    let expected = "\
+        [2018-12-03 10:00:00, 60] Day work task1\n\
+        [2018-12-03 11:00:00, 30] Day work task2\n\
+        [2018-12-03 12:00:00, 60] Some work\n\
+        [2018-12-04 09:00:00, 60] Other work\n";
+    assert_content!(journal_file, expected);
+
+    run!("tt-cli set --force --match \"^Day work\" --all-matches --yes time +1h");
+    let expected = "\
+        [2018-12-03 11:00:00, 60] Day work task1\n\
+        [2018-12-03 12:00:00, 30] Day work task2\n\
+        [2018-12-03 12:00:00, 60] Some work\n\
+        [2018-12-04 09:00:00, 60] Other work\n";
+    assert_content!(journal_file, expected);
+}
+
+#[test]
+fn select_shared_id() {
+    let test_paths = TestPaths::new("test_select_shared_id");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+
+    let content = "\
+        [2018-12-03 09:00:00, 5] Day work task1\n\
+        [2018-12-03 09:00:00, 60] Day work task2\n\
+        [2018-12-03 10:00:00, 30] Some work\n";
+    create_file!(journal_file, content);
+
+    run!("tt-cli del --id 20181203090000");
+    assert_content!(journal_file, content);
+
+    run!("tt-cli del --id 20181203100000");
+    let expected = "\
+        [2018-12-03 09:00:00, 5] Day work task1\n\
+        [2018-12-03 09:00:00, 60] Day work task2\n";
+    assert_content!(journal_file, expected);
+}
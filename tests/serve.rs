@@ -0,0 +1,77 @@
+#[macro_use]
+mod common;
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::TcpStream;
+use std::process::{Command, Stdio};
+use file_assertions::create_file;
+use crate::common::TestPaths;
+
+fn request(address: &str, method: &str, path: &str, body: &str) -> String {
+    request_with(address, method, path, &format!("Host: {}\r\nContent-Type: application/json", address), body)
+}
+
+fn request_with(address: &str, method: &str, path: &str, headers: &str, body: &str) -> String {
+    let mut stream = TcpStream::connect(address).expect("Can't connect to the server");
+    let request = format!("{} {} HTTP/1.1\r\n{}\r\nContent-Length: {}\r\n\r\n{}", method, path, headers, body.len(), body);
+    stream.write_all(request.as_bytes()).expect("Can't send request");
+    // The server may reset the connection after the response when it rejects the request without reading it all
+    let mut response = String::new();
+    stream.read_to_string(&mut response).ok();
+    response
+}
+
+#[test]
+fn serve_api() {
+    let test_paths = TestPaths::new("test_serve");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+
+    let content = "\
+        [2018-12-03 09:00:00, 60] Day work task1\n\
+        [2018-12-03 11:00:00, 30] Day work task2\n";
+    create_file!(journal_file, content);
+    run!("tt-cli start Meetings standup");
+
+    // The server listens on a free port chosen by the system and prints its address once it accepts connections
+    let mut server = Command::new(target_path!("tt-cli"))
+        .args(vec!["serve", "--port", "0"])
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("Failed execute server");
+    let mut listening = String::new();
+    BufReader::new(server.stdout.as_mut().expect("Can't get server output"))
+        .read_line(&mut listening)
+        .expect("Can't read server output");
+    let address = listening.trim().trim_start_matches("Listening on http://");
+
+    let report = request(address, "GET", "/report?from=2018-12-03", "");
+    let bad_date = request(address, "GET", "/records?from=2018-13-40", "");
+    let status = request(address, "GET", "/status", "");
+    let started = request(address, "POST", "/start", r#"{"note": "Day work task3"}"#);
+    let stopped = request(address, "POST", "/stop", "");
+    let stopped_again = request(address, "POST", "/stop", "");
+    let missing = request(address, "GET", "/missing", "");
+    let cross_origin = request_with(
+        address, "POST", "/start", &format!("Host: {}\r\nContent-Type: application/json\r\nOrigin: http://evil.example", address), "",
+    );
+    let rebound = request_with(address, "GET", "/records", "Host: evil.example", "");
+    let form = request_with(address, "POST", "/stop", &format!("Host: {}\r\nContent-Type: text/plain", address), "");
+    let large = request_with(address, "GET", "/status", &format!("Host: {}\r\nX-Padding: {}", address, "x".repeat(20000)), "");
+    server.kill().expect("Can't stop server");
+    server.wait().expect("Can't wait for server");
+
+    assert!(report.starts_with("HTTP/1.1 200 OK"), "{}", report);
+    assert!(report.contains(r#""total":90"#), "{}", report);
+    assert!(report.contains(r#""note":"Day work""#), "{}", report);
+    assert!(bad_date.starts_with("HTTP/1.1 400 Bad Request"), "{}", bad_date);
+    assert!(status.contains(r#""running":true"#) && status.contains(r#""note":"Meetings standup""#), "{}", status);
+    assert!(started.contains(r#""running":true"#) && started.contains(r#""note":"Day work task3""#), "{}", started);
+    assert!(stopped.contains(r#""running":false"#), "{}", stopped);
+    assert!(stopped_again.starts_with("HTTP/1.1 409 Conflict"), "{}", stopped_again);
+    assert!(missing.starts_with("HTTP/1.1 404 Not Found"), "{}", missing);
+    assert!(cross_origin.starts_with("HTTP/1.1 403 Forbidden"), "{}", cross_origin);
+    assert!(rebound.starts_with("HTTP/1.1 403 Forbidden"), "{}", rebound);
+    assert!(form.starts_with("HTTP/1.1 415 Unsupported Media Type"), "{}", form);
+    assert!(large.starts_with("HTTP/1.1 400 Bad Request"), "{}", large);
+}
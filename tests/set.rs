@@ -0,0 +1,143 @@
+#[macro_use]
+mod common;
+
+use file_assertions::{create_file, assert_content};
+use crate::common::TestPaths;
+
+#[test]
+fn set_overlapping_time() {
+    let test_paths = TestPaths::new("test_set_overlapping_time");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+
+    let content = "\
+        [2018-12-03 09:00:00, 60] record1\n\
+        [2018-12-03 10:20:00, 30] record2\n\
+        [2018-12-03 11:00:00, 15] record3\n";
+    create_file!(journal_file, content);
+
+    run!("tt-cli set -n 1 time 09:50");
+    assert_content!(journal_file, content);
+
+    run!("tt-cli set -n 1 time 10:40");
+    assert_content!(journal_file, content);
+
+    run!("tt-cli set -n 1 time 10:05");
+    let expected = "\
+        [2018-12-03 09:00:00, 60] record1\n\
+        [2018-12-03 10:05:00, 30] record2\n\
+        [2018-12-03 11:00:00, 15] record3\n";
+    assert_content!(journal_file, expected);
+
+    run!("tt-cli set --force -n 1 time 09:50");
+    let expected = "\
+        [2018-12-03 09:00:00, 60] record1\n\
+        [2018-12-03 09:50:00, 30] record2\n\
+        [2018-12-03 11:00:00, 15] record3\n";
+    assert_content!(journal_file, expected);
+}
+
+#[test]
+fn set_overlapping_activity() {
+    let test_paths = TestPaths::new("test_set_overlapping_activity");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+
+    let content = "\
+        [2018-12-03 09:00:00, 60] record1\n\
+        [2018-12-03 10:20:00, 30] record2\n";
+    create_file!(journal_file, content);
+
+    run!("tt-cli set -n 1 act 90");
+    assert_content!(journal_file, content);
+
+    run!("tt-cli set -n 1 rest 30");
+    assert_content!(journal_file, content);
+
+    run!("tt-cli set -n 1 act 75");
+    let expected = "\
+        [2018-12-03 09:00:00, 75] record1\n\
+        [2018-12-03 10:20:00, 30] record2\n";
+    assert_content!(journal_file, expected);
+}
+
+#[test]
+fn set_time_by_overlapped_time() {
+    let test_paths = TestPaths::new("test_set_time_by_overlapped_time");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+
+    let content = "\
+        [2018-12-03 09:00:00, 60] record1\n\
+        [2018-12-03 10:00:00, 60] record2\n\
+        [2018-12-03 12:00:00, 60] record3\n\
+        [2018-12-03 14:00:00, 60] record4\n";
+    create_file!(journal_file, content);
+
+    run!("tt-cli set -n 2 time 14:30");
+    assert_content!(journal_file, content);
+
+    run!("tt-cli set -n 2 time 13:00");
+    let expected = "\
+        [2018-12-03 09:00:00, 60] record1\n\
+        [2018-12-03 13:00:00, 60] record2\n\
+        [2018-12-03 12:00:00, 60] record3\n\
+        [2018-12-03 14:00:00, 60] record4\n";
+    assert_content!(journal_file, expected);
+}
+
+#[test]
+fn set_end() {
+    let test_paths = TestPaths::new("test_set_end");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+
+    let content = "\
+        [2018-12-03 09:00:00, ] record1\n\
+        [2018-12-03 10:20:00, 30 (5)] record2\n";
+    create_file!(journal_file, content);
+
+    run!("tt-cli set -n 1 end 09:45");
+    let expected = "\
+        [2018-12-03 09:00:00, 45] record1\n\
+        [2018-12-03 10:20:00, 30 (5)] record2\n";
+    assert_content!(journal_file, expected);
+
+    run!("tt-cli set end 2018-12-03 17:40");
+    let expected = "\
+        [2018-12-03 09:00:00, 45] record1\n\
+        [2018-12-03 10:20:00, 435 (5)] record2\n";
+    assert_content!(journal_file, expected);
+
+    assert_output!("tt-cli list --end 2018-12-03" => "
+09:45:00  [2018-12-03 09:00:00, 45] record1
+17:40:00  [2018-12-03 10:20:00, 435 (5)] record2
+");
+}
+
+#[test]
+fn set_duration() {
+    let test_paths = TestPaths::new("test_set_duration");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+
+    create_file!(journal_file, "[2018-12-03 09:00:00, 30] record1\n");
+
+    run!("tt-cli set act 1h30m");
+    assert_content!(journal_file, "[2018-12-03 09:00:00, 90] record1\n");
+
+    run!("tt-cli set act 1.5h");
+    assert_content!(journal_file, "[2018-12-03 09:00:00, 90] record1\n");
+
+    run!("tt-cli set act +15m");
+    assert_content!(journal_file, "[2018-12-03 09:00:00, 105] record1\n");
+
+    run!("tt-cli set act -10m");
+    assert_content!(journal_file, "[2018-12-03 09:00:00, 95] record1\n");
+
+    run!("tt-cli set rest 0:20");
+    assert_content!(journal_file, "[2018-12-03 09:00:00, 95 (20)] record1\n");
+
+    run!("tt-cli set rest -5");
+    assert_content!(journal_file, "[2018-12-03 09:00:00, 95 (15)] record1\n");
+}
@@ -51,3 +51,53 @@ fn start_record() {
     );
     assert_content!(journal_file, expected);
 }
+
+#[test]
+fn start_record_for() {
+    let test_paths = TestPaths::new("test_start_for");
+    let journal = test_paths.init();
+    let journal_file = test_paths.journal_file();
+
+    run!("tt-cli start --for 1s --wait record1");
+
+    let record = journal.get(&[], Some(-1))
+        .expect(&format!("Can't get record from {:?}", journal_file))
+        .expect(&format!("The record in {:?} is empty", journal_file));
+    assert_eq!("record1", record.note);
+    assert!(record.activity.is_some());
+
+    run!("tt-cli start --for 45m record2");
+
+    let record = journal.get(&[], Some(-1))
+        .expect(&format!("Can't get record from {:?}", journal_file))
+        .expect(&format!("The record in {:?} is empty", journal_file));
+    let expected = format!(
+        "[{}, ] record2\nElapsed 0:00, 0:45 left of 0:45",
+        record.start.unwrap().format(Record::START_DATETIME_FORMAT)
+    );
+    assert_output!("tt-cli status" => expected);
+}
+
+#[test]
+fn planned_record_moved_and_deleted() {
+    let test_paths = TestPaths::new("test_start_for_moved");
+    let journal = test_paths.init();
+    let journal_file = test_paths.journal_file();
+    let mut meta_file = journal_file.as_os_str().to_owned();
+    meta_file.push(".meta");
+
+    run!("tt-cli start --for 45m record1");
+    run!("tt-cli set time -10m");
+
+    let record = journal.get(&[], Some(-1))
+        .expect(&format!("Can't get record from {:?}", journal_file))
+        .expect(&format!("The record in {:?} is empty", journal_file));
+    let expected = format!(
+        "[{}, ] record1\nElapsed 0:10, 0:35 left of 0:45",
+        record.start.unwrap().format(Record::START_DATETIME_FORMAT)
+    );
+    assert_output!("tt-cli status" => expected);
+
+    run!("tt-cli del");
+    assert_content!(meta_file, "");
+}
@@ -0,0 +1,25 @@
+#[macro_use]
+mod common;
+
+use file_assertions::create_file;
+use tt_core::record::{Local, Record};
+use crate::common::TestPaths;
+
+#[test]
+fn status_format() {
+    let test_paths = TestPaths::new("test_status");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+    let today = Local::today().and_hms(0, 0, 0).format(Record::START_DATETIME_FORMAT);
+    let now = Local::now().format(Record::START_DATETIME_FORMAT);
+
+    let content = format!("[2018-12-03 09:00:00, 30] Day work task1\n[{}, 75] Day work task2\n", today);
+    create_file!(journal_file, &content);
+    assert_output!("tt-cli status --format \"{project} {elapsed} ({today_total})\"" => "(1:15)");
+
+    let content = format!("{}[{}, ] Meetings standup\n", content, now);
+    create_file!(journal_file, &content);
+    assert_output!(
+        "tt-cli status --format \"{project}: {note} {elapsed} ({today_total})\"" => "Meetings: Meetings standup 0:00 (1:15)"
+    );
+}
@@ -2,7 +2,7 @@
 mod common;
 
 use file_assertions::{create_file, assert_content};
-use tt_core::record::{Record, Duration};
+use tt_core::record::{Record, Duration, Local};
 use tt_core::journal::Journal;
 use crate::common::TestPaths;
 
@@ -59,3 +59,119 @@ fn stop_record() {
     record.note = "record1".to_string();
     assert_eq!(record, first_record);
 }
+
+#[test]
+fn stop_idle_record() {
+    let test_paths = TestPaths::new("test_stop_idle");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+    let (_, config_file, _) = test_paths.paths();
+
+    let config_content = format!("journal_file = {:?}\nidle_threshold = 60\n", journal_file.as_os_str());
+    create_file!(config_file, config_content);
+
+    let mut record = Record::now();
+    record.start.as_mut().map(|start| *start = *start - Duration::minutes(150));
+    let start = record.start.unwrap().format(Record::START_DATETIME_FORMAT);
+    let end = (record.start.unwrap() + Duration::minutes(90)).format(Record::START_DATETIME_FORMAT);
+
+    let content = format!("[{}, ]", start);
+    create_file!(journal_file, &content);
+
+    run!("tt-cli stop --idle rest record1");
+    let expected = format!("[{}, 60 (90)] record1\n", start);
+    assert_content!(journal_file, expected);
+
+    create_file!(journal_file, &content);
+
+    let command = &format!("tt-cli stop --idle \"{}\" record1", end);
+    run!(command);
+    let expected = format!("[{}, 90] record1\n", start);
+    assert_content!(journal_file, expected);
+
+    create_file!(journal_file, &content);
+
+    run!("tt-cli stop record1");
+    let expected = format!("[{}, 150] record1\n", start);
+    assert_content!(journal_file, expected);
+}
+
+#[test]
+fn stop_idle_record_over_midnight() {
+    let test_paths = TestPaths::new("test_stop_idle_midnight");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+    let (_, config_file, _) = test_paths.paths();
+
+    let config_content = format!("journal_file = {:?}\nidle_threshold = 30\n", journal_file.as_os_str());
+    create_file!(config_file, config_content);
+
+    let start = Local::today().and_hms(0, 0, 0) - Duration::minutes(60);
+    let start = start.format(Record::START_DATETIME_FORMAT);
+    let content = format!("[{}, ]", start);
+    create_file!(journal_file, &content);
+
+    let command = &format!("tt-cli stop --idle \"{} 22:30\" record1", Local::today().pred().format("%Y-%m-%d"));
+    run!(command);
+    assert_content!(journal_file, content);
+
+    run!("tt-cli stop --idle 00:30 record1");
+    let expected = format!("[{}, 90] record1\n", start);
+    assert_content!(journal_file, expected);
+}
+
+#[test]
+fn stop_idle_record_in_future() {
+    let test_paths = TestPaths::new("test_stop_idle_future");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+    let (_, config_file, _) = test_paths.paths();
+
+    let config_content = format!("journal_file = {:?}\nidle_threshold = 30\n", journal_file.as_os_str());
+    create_file!(config_file, config_content);
+
+    let start = Local::now() - Duration::minutes(60);
+    let content = format!("[{}, ]", start.format(Record::START_DATETIME_FORMAT));
+    create_file!(journal_file, &content);
+
+    let command = &format!("tt-cli stop --idle \"{} 10:00\" record1", Local::today().succ().format("%Y-%m-%d"));
+    run!(command);
+    assert_content!(journal_file, content);
+}
+
+#[test]
+fn stop_capped_record() {
+    let test_paths = TestPaths::new("test_stop_capped");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+    let (_, config_file, _) = test_paths.paths();
+
+    let config_content = format!("journal_file = {:?}\nmax_activity = 120\n", journal_file.as_os_str());
+    create_file!(config_file, config_content);
+
+    let mut record = Record::now();
+    record.start.as_mut().map(|start| *start = *start - Duration::minutes(600));
+    let start = record.start.unwrap().format(Record::START_DATETIME_FORMAT);
+    let previous = (record.start.unwrap() - Duration::minutes(60)).format(Record::START_DATETIME_FORMAT);
+
+    let content = format!("[{}, 30] record0\n[{}, ]", previous, start);
+    create_file!(journal_file, content);
+
+    run!("tt-cli stop record1");
+    let expected = format!("[{}, 30] record0\n[{}, 120] record1\n", previous, start);
+    assert_content!(journal_file, expected);
+
+    let flagged = format!("[{}, 120] record1  (capped from 600)", start);
+    assert_output!("tt-cli list --flagged" => flagged);
+
+    run!("tt-cli set --match record --all-matches act 150");
+    assert_content!(journal_file, expected);
+    assert_output!("tt-cli list --flagged" => flagged);
+
+    run!("tt-cli set note record2");
+    let flagged = format!("[{}, 120] record2  (capped from 600)", start);
+    assert_output!("tt-cli list --flagged" => flagged);
+
+    run!("tt-cli set act 150");
+    assert_output!("tt-cli list --flagged" => "");
+}
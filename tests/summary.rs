@@ -0,0 +1,49 @@
+#[macro_use]
+mod common;
+
+use file_assertions::create_file;
+use crate::common::TestPaths;
+
+#[test]
+fn summary_week_and_month() {
+    let test_paths = TestPaths::new("test_summary");
+    test_paths.init();
+    let journal_file = test_paths.journal_file();
+
+    let content = "\
+        [2018-11-30 09:00:00, 240] Day work task0\n\
+        [2018-12-03 09:00:00, 300] Day work task1\n\
+        [2018-12-03 14:30:00, 15] Meetings standup\n\
+        [2018-12-04 09:00:00, 240] Day work task2\n\
+        [2018-12-10 09:00:00, 60] Day work task3\n";
+    create_file!(journal_file, content);
+
+    assert_output!("tt-cli summary 2018-12-05" => "\
+        2018-12-03 - 2018-12-09\n\
+        Mon 2018-12-03    5:15\n\
+        Tue 2018-12-04    4:00\n\
+        Wed 2018-12-05    0:00\n\
+        Thu 2018-12-06    0:00\n\
+        Fri 2018-12-07    0:00\n\
+        Sat 2018-12-08    0:00\n\
+        Sun 2018-12-09    0:00\n\
+        ----------------------\n\
+        Total             9:15\n\
+        Average           4:37 per day of 2 days with activity\n\
+        Top projects:\n    9:00  Day\n    0:15  Meetings\n\
+        Previous week 4:00, +5:15 (+315 minutes, +131.2%)");
+
+    assert_output!("tt-cli summary --month 2018-12-05" => "\
+        2018-12-01 - 2018-12-31\n\
+        12-01 - 12-02     0:00\n\
+        12-03 - 12-09     9:15\n\
+        12-10 - 12-16     1:00\n\
+        12-17 - 12-23     0:00\n\
+        12-24 - 12-30     0:00\n\
+        12-31 - 12-31     0:00\n\
+        ----------------------\n\
+        Total            10:15\n\
+        Average           3:25 per day of 3 days with activity\n\
+        Top projects:\n   10:00  Day\n    0:15  Meetings\n\
+        Previous month 4:00, +6:15 (+375 minutes, +156.2%)");
+}